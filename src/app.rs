@@ -1,30 +1,50 @@
 use std::{
     rc::Rc,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
-use super::ui::{requests_list::RequestsList, right::RightState};
+use super::ui::{
+    requests_list::{NameEdit, RequestsList},
+    right::RightState,
+};
 use crate::{
+    client::HttpClient,
+    clipboard,
+    collection::{Collection, SavedRequest},
     component::Component,
-    http::http_request,
+    config::Config,
+    cookies::{self, Cookie},
+    curl, environment, extract,
+    history::{History, HistoryEntry},
+    http::{PreparedRequest, ReceivedResponse},
+    keys::{Binding, GlobalKeyAction, GlobalKeymap, KeyChord, Keymap},
     pane::Pane,
+    runner::{self, RequestResult, RunReport, Selection},
     ui::{
+        environments::EnvironmentsList,
+        help::HelpState,
+        history_list::HistoryList,
         methods_list::MethodsList,
+        mouse::{self, SCROLL_ROWS},
+        report::ReportState,
         right::RightStatePane,
-        theme::{GlobalTheme, Theme},
+        theme::{GlobalTheme, Theme, Themes},
     },
 };
-use crossterm::event::KeyEvent;
-use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Response,
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use reqwest::{header::HeaderMap, StatusCode, Url};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::Instant,
 };
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum PaneType {
     #[default]
     RequestList,
+    History,
     Right(RightStatePane),
 }
 
@@ -45,25 +65,111 @@ pub enum InputMode {
     /// Only keybinds for editing mode are active
     Hostname,
     Body,
+    /// Renaming an item in the requests list
+    Name,
+    /// Navigating or editing a key/value table
+    Table,
+    /// Editing the authentication of a request
+    Auth,
+    /// Typing a search in the response
+    Search,
+    /// Typing a filter for a JSON response
+    Filter,
 }
 
 #[derive(Debug)]
 pub enum Action {
     MoveRelative(Movement),
+    SelectRequest(usize),
+    NewRequest,
+    DuplicateRequest(usize),
+    DeleteRequest(usize),
+    RenameRequest(usize, String),
+    /// Edit the folder of a request of the collection
+    EditFolder(usize),
+    /// Move a request to a folder, the top level when it is empty
+    MoveToFolder(usize, String),
+    /// Copy a request of the collection as a curl command
+    ExportCurl(usize),
+    /// Add a request from a curl command in the clipboard
+    ImportCurl,
+    SaveCollection,
+    /// Show the response of a history entry
+    ViewHistory(usize),
+    /// Load the request of a history entry into the editor
+    RestoreHistory(usize),
+    AddHistoryToCollection(usize),
+    SaveHistory,
+    /// Stop running the collection
+    CancelRun,
+    /// Show an error in the status line of the response until the next key
+    Error(String),
+}
+
+/// A request that is being sent in the background
+struct InFlight {
+    id: u64,
+    handle: JoinHandle<()>,
+    /// The request with its variables substituted, for the history
+    request: SavedRequest,
+    sent: SystemTime,
+}
+
+type ResponseMessage = (u64, Result<ReceivedResponse, String>);
+
+/// A run of the collection in the background
+struct Run {
+    id: u64,
+    handle: JoinHandle<()>,
+}
+
+/// Progress of running a collection in the background
+enum RunMessage {
+    Result(RequestResult),
+    Finished(RunReport),
 }
 
 pub struct App<'a> {
-    pub requests_list: RequestsList<&'a str>,
+    pub requests_list: RequestsList<'a, String>,
     pub right_state: RightState<'a>,
     pub theme: GlobalTheme,
+    themes: Themes,
     active_pane_type: PaneType,
     pub methods_list: MethodsList,
+    pub environments_list: EnvironmentsList<'a>,
+    pub history_list: HistoryList,
+    pub report: ReportState,
+    pub help: HelpState,
+    /// The key bindings of the config
+    pub keymap: GlobalKeymap,
+    /// Keys pressed so far of a key sequence
+    pub pending_keys: Vec<KeyChord>,
+    pub collection: Collection,
+    /// Shared by all requests so that connections are reused
+    client: HttpClient,
+    /// Index of the request in `collection` that is currently loaded in the editor
+    loaded_request: Option<usize>,
+    in_flight: Option<InFlight>,
+    request_id: u64,
+    run: Option<Run>,
+    run_id: u64,
+    response_sender: UnboundedSender<ResponseMessage>,
+    response_receiver: UnboundedReceiver<ResponseMessage>,
+    run_sender: UnboundedSender<(u64, RunMessage)>,
+    run_receiver: UnboundedReceiver<(u64, RunMessage)>,
 }
 
 impl<'a> App<'a> {
-    pub fn new(theme: Theme) -> App<'a> {
-        let theme = Rc::new(theme);
-        let requests_list = RequestsList::new(vec!["Request 1", "Request 2", "Request 3"]);
+    pub fn new(
+        themes: Themes,
+        client: HttpClient,
+        collection: Collection,
+        history: History,
+        keymap: Keymap,
+    ) -> App<'a> {
+        let theme = Rc::new(Theme::new(themes.palette()));
+        let keymap = Rc::new(keymap);
+        let requests_list = RequestsList::new(collection.names());
         let selected_method = Arc::new(Mutex::new(reqwest::Method::GET));
         let methods_list = MethodsList::new(
             vec![
@@ -77,40 +183,363 @@ impl<'a> App<'a> {
             ],
             selected_method.clone(),
         );
-        let right_state = RightState::new(theme.clone(), selected_method.clone());
+        let right_state = RightState::new(
+            theme.clone(),
+            keymap.clone(),
+            selected_method.clone(),
+            collection.cookie_jar.clone(),
+            collection.environments.clone(),
+        );
+        let environments_list = EnvironmentsList::new(
+            theme.clone(),
+            keymap.clone(),
+            collection.environments.clone(),
+        );
+        let history_list = HistoryList::new(theme.clone(), history);
+        let report = ReportState::new(theme.clone(), keymap.clone());
+        let help = HelpState::new(theme.clone(), &keymap);
+
+        let (response_sender, response_receiver) = mpsc::unbounded_channel();
+        let (run_sender, run_receiver) = mpsc::unbounded_channel();
 
         let mut app = App {
             requests_list,
             right_state,
             active_pane_type: PaneType::RequestList,
             theme,
+            themes,
             methods_list,
+            environments_list,
+            history_list,
+            report,
+            help,
+            keymap,
+            pending_keys: vec![],
+            collection,
+            client,
+            loaded_request: None,
+            in_flight: None,
+            run: None,
+            run_id: 0,
+            request_id: 0,
+            response_sender,
+            response_receiver,
+            run_sender,
+            run_receiver,
         };
+        if let Some(i) = app.requests_list.state.selected() {
+            app.load_request(i);
+        }
         app.active_pane().set_active(true);
         app
     }
 
-    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+    /// Input mode of the open popup or else the active pane
+    pub fn input_mode(&mut self) -> InputMode {
         if self.methods_list.visible() {
-            self.methods_list.handle_key(key_event);
+            self.methods_list.input_mode()
+        } else if self.help.visible() {
+            self.help.input_mode()
+        } else if self.report.visible() {
+            self.report.input_mode()
+        } else if self.environments_list.visible() {
+            self.environments_list.input_mode()
+        } else {
+            self.active_pane().input_mode()
+        }
+    }
+
+    /// Passes a key to the open popup or else the active pane, `sequence` is the action of
+    /// the key sequence that the key completed
+    pub fn handle_key_event(&mut self, key_event: KeyEvent, sequence: Option<Binding>) {
+        let help = Some(Binding::Global(GlobalKeyAction::Help));
+        let binding = match sequence.or_else(|| self.keymap.lookup(key_event, true)) {
+            // The help key also closes the help, which takes all other keys
+            global if self.help.visible() && global == help => global,
+            _ => sequence.or_else(|| self.keymap.lookup(key_event, false)),
+        };
+        self.right_state.response_state.error = None;
+        if self.methods_list.visible() {
+            self.methods_list.handle_key(key_event, binding);
             return;
         }
-        if let Some(action) = self.active_pane().handle_key(key_event) {
-            match action {
-                Action::MoveRelative(dir) => {
-                    // TODO: move .relative_pane() into .handle_key()
-                    if let Some(pane) = self.active_pane().relative_pane(dir) {
-                        self.activate_pane(pane);
+        let action = if self.help.visible() {
+            self.help.handle_key(key_event, binding)
+        } else if self.report.visible() {
+            self.report.handle_key(key_event, binding)
+        } else if self.environments_list.visible() {
+            self.environments_list.handle_key(key_event, binding)
+        } else {
+            self.active_pane().handle_key(key_event, binding)
+        };
+        if let Some(action) = action {
+            self.handle_action(action);
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::MoveRelative(dir) => {
+                // TODO: move .relative_pane() into .handle_key()
+                if let Some(pane) = self.active_pane().relative_pane(dir) {
+                    self.activate_pane(pane);
+                }
+            }
+            Action::SelectRequest(i) => {
+                self.store_request();
+                self.load_request(i);
+            }
+            Action::NewRequest => {
+                self.store_request();
+                self.collection.requests.push(SavedRequest::default());
+                self.reload_requests_list(self.collection.requests.len() - 1);
+            }
+            Action::DuplicateRequest(i) => {
+                self.store_request();
+                if let Some(copy) = self.collection.duplicate(i) {
+                    self.reload_requests_list(copy);
+                }
+            }
+            Action::DeleteRequest(i) => {
+                if self.collection.remove(i).is_some() {
+                    // The removed request should not be stored back
+                    self.loaded_request = None;
+                    if self.collection.requests.is_empty() {
+                        self.collection.requests.push(SavedRequest::default());
+                    }
+                    self.reload_requests_list(i.min(self.collection.requests.len() - 1));
+                }
+            }
+            Action::RenameRequest(i, name) => {
+                if let Some(request) = self.collection.requests.get_mut(i) {
+                    request.name = name;
+                }
+                self.reload_requests_list(i);
+            }
+            Action::EditFolder(i) => {
+                if let Some(request) = self.collection.requests.get(i) {
+                    self.requests_list.edit(NameEdit::Folder, &request.folder);
+                }
+            }
+            Action::MoveToFolder(i, folder) => {
+                self.store_request();
+                if let Some(request) = self.collection.requests.get_mut(i) {
+                    request.folder = folder
+                        .split('/')
+                        .map(str::trim)
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                }
+                self.reload_requests_list(i);
+            }
+            Action::ExportCurl(i) => {
+                self.store_request();
+                // Variables stay `{{name}}`, so their values, like tokens, are not copied
+                let exported = self.collection.requests.get(i).map(curl::export);
+                if let Some(Err(e)) = exported.map(clipboard::copy) {
+                    self.right_state.response_state.error = Some(e);
+                }
+            }
+            Action::ImportCurl => {
+                let command = match clipboard::paste() {
+                    Ok(command) => command,
+                    Err(e) => {
+                        self.right_state.response_state.error = Some(e);
+                        return;
+                    }
+                };
+                match curl::parse(&command) {
+                    Ok(request) => {
+                        self.store_request();
+                        self.collection.requests.push(request);
+                        self.reload_requests_list(self.collection.requests.len() - 1);
+                    }
+                    Err(e) => self
+                        .right_state
+                        .response_state
+                        .set_response(format!("Failed to import curl command: {e}")),
+                }
+            }
+            Action::SaveCollection => self.save_collection(),
+            Action::ViewHistory(i) => {
+                if let Some(entry) = self.history_list.history.entries.get(i).cloned() {
+                    match entry.response {
+                        Ok(response) => {
+                            self.display_response(
+                                StatusCode::from_u16(response.status).unwrap_or_default(),
+                                entry.time,
+                                response.header_map(),
+                                response.body,
+                                Url::parse(&response.url).ok(),
+                            );
+                            self.right_state.response_state.timing = entry.timing;
+                        }
+                        Err(e) => self.right_state.response_state.set_response(e),
                     }
                 }
-                _ => (),
             }
+            Action::RestoreHistory(i) => {
+                if let Some(entry) = self.history_list.history.entries.get(i).cloned() {
+                    // The entry is opened as an unsaved request, its resolved variables must not
+                    // replace the templates of a saved request
+                    self.store_request();
+                    self.loaded_request = None;
+                    self.requests_list.state.select(None);
+                    self.methods_list.select_method(entry.request.method());
+                    self.right_state.request_state.load(&entry.request);
+                }
+            }
+            Action::AddHistoryToCollection(i) => {
+                if let Some(entry) = self.history_list.history.entries.get(i) {
+                    let request = entry.request.clone();
+                    self.store_request();
+                    self.collection.requests.push(request);
+                    self.reload_requests_list(self.collection.requests.len() - 1);
+                }
+            }
+            Action::SaveHistory => self.save_history(),
+            Action::CancelRun => self.cancel_run(),
+            Action::Error(e) => self.right_state.response_state.error = Some(e),
+        }
+    }
+
+    /// Focuses and selects what was clicked, the wheel scrolls the body and the response
+    pub fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.click(column, row),
+            MouseEventKind::ScrollUp => self.scroll(column, row, -(SCROLL_ROWS as i16)),
+            MouseEventKind::ScrollDown => self.scroll(column, row, SCROLL_ROWS as i16),
+            _ => (),
+        }
+    }
+
+    fn popup_visible(&self) -> bool {
+        self.methods_list.visible()
+            || self.help.visible()
+            || self.report.visible()
+            || self.environments_list.visible()
+    }
+
+    fn click(&mut self, column: u16, row: u16) {
+        if self.methods_list.visible() {
+            let len = self.methods_list.items.len();
+            if let Some(i) = self.methods_list.list_area.item_at(column, row, len, 1) {
+                let method = self.methods_list.items[i].clone();
+                self.methods_list.select_method(method);
+                self.methods_list.toggle_visible();
+            }
+            return;
+        }
+        // Clicks do not reach the panes under a popup and do not leave inputs, like the global keys
+        if self.popup_visible() || self.input_mode() != InputMode::Normal {
+            return;
+        }
+        let request_state = &self.right_state.request_state;
+        let response_state = &self.right_state.response_state;
+        if self.requests_list.visible()
+            && mouse::contains(self.requests_list.list_area.area, column, row)
+        {
+            self.activate_pane(PaneType::RequestList);
+            let len = self.requests_list.items.len();
+            if let Some(i) = self.requests_list.list_area.item_at(column, row, len, 1) {
+                self.requests_list.state.select(Some(i));
+                self.handle_action(Action::SelectRequest(i));
+            }
+        } else if self.requests_list.visible()
+            && mouse::contains(self.history_list.list_area.area, column, row)
+        {
+            self.activate_pane(PaneType::History);
+            self.history_list.click(column, row);
+        } else if mouse::contains(request_state.area, column, row) {
+            self.activate_pane(PaneType::Right(RightStatePane::Request));
+            self.right_state.request_state.click(column, row);
+        } else if mouse::contains(response_state.area, column, row) {
+            self.activate_pane(PaneType::Right(RightStatePane::Response));
+            self.right_state.response_state.click(column, row);
+        }
+    }
+
+    fn scroll(&mut self, column: u16, row: u16, rows: i16) {
+        if self.popup_visible() {
+            return;
+        }
+        let right_state = &mut self.right_state;
+        if mouse::contains(right_state.request_state.area, column, row) {
+            right_state.request_state.scroll_by(rows);
+        } else if mouse::contains(right_state.response_state.area, column, row) {
+            right_state.response_state.scroll_by(rows);
+        }
+    }
+
+    /// Stores the editor into the loaded request of the collection
+    pub fn store_request(&mut self) {
+        let request = self.current_request();
+        if let Some(loaded) = self
+            .loaded_request
+            .and_then(|i| self.collection.requests.get_mut(i))
+        {
+            *loaded = request;
+        }
+    }
+
+    /// Loads the request at `index` of the collection into the editor
+    fn load_request(&mut self, index: usize) {
+        if let Some(request) = self.collection.requests.get(index) {
+            self.methods_list.select_method(request.method());
+            self.right_state.request_state.load(request);
+            self.loaded_request = Some(index);
+        }
+    }
+
+    fn reload_requests_list(&mut self, select: usize) {
+        self.requests_list.set_items(self.collection.names());
+        self.requests_list.select(select);
+        self.load_request(select);
+        self.save_collection();
+    }
+
+    /// Shows the cookies of the last response and keeps them in the jar when it is enabled
+    pub fn store_cookies(&mut self, cookies: Vec<Cookie>) {
+        let stored = match self.collection.cookie_jar.lock() {
+            Ok(mut jar) if jar.enabled => {
+                jar.store(&cookies);
+                true
+            }
+            _ => false,
+        };
+        self.right_state
+            .response_state
+            .cookies
+            .set_received(cookies);
+        if stored {
+            self.save_collection();
+        }
+    }
+
+    /// Writes the collection to disk, reporting failures in the response pane
+    pub fn save_collection(&mut self) {
+        if let Err(e) = self.collection.save() {
+            self.right_state
+                .response_state
+                .set_response(format!("Failed to save collection: {e}"));
+        }
+    }
+
+    /// Writes the history to disk, reporting failures in the response pane
+    pub fn save_history(&mut self) {
+        if let Err(e) = self.history_list.history.save() {
+            self.right_state
+                .response_state
+                .set_response(format!("Failed to save history: {e}"));
         }
     }
 
     pub fn active_pane(&mut self) -> &mut dyn Pane {
         match self.active_pane_type {
             PaneType::RequestList => self.requests_list.active_pane(&self.active_pane_type),
+            PaneType::History => self.history_list.active_pane(&self.active_pane_type),
             PaneType::Right(_) => self.right_state.active_pane(&self.active_pane_type),
         }
     }
@@ -122,39 +551,297 @@ impl<'a> App<'a> {
             self.active_pane().set_active(true);
         }
     }
-    pub async fn send_request(&mut self) -> Result<(Response, Duration), String> {
-        match self.methods_list.selected() {
-            Some(method) => {
-                let uri = self
-                    .right_state
-                    .request_state
-                    .input_line
-                    .clone()
-                    .into_lines()
-                    .join("\n");
-
-                let body = self
-                    .right_state
-                    .request_state
-                    .body
-                    .clone()
-                    .into_lines()
-                    .join("\n");
-
-                let resp = http_request(
-                    method,
-                    uri,
-                    HeaderMap::new(),
-                    HeaderValue::from_str("application/json").unwrap(),
-                    body,
-                )
+    /// The request in the editor, as it would be stored in the collection
+    pub fn current_request(&self) -> SavedRequest {
+        let mut request = self
+            .loaded_request
+            .and_then(|i| self.collection.requests.get(i).cloned())
+            .unwrap_or_default();
+        if let Ok(method) = self.right_state.request_state.selected_method().lock() {
+            request.method = method.to_string();
+        }
+        self.right_state.request_state.store(&mut request);
+        request
+    }
+
+    pub fn sending(&self) -> bool {
+        self.in_flight.is_some()
+    }
+
+    /// Switches to the next theme, the response is styled again with its colours
+    pub fn next_theme(&mut self) {
+        self.theme.set_palette(self.themes.next());
+        self.right_state.response_state.restyle();
+    }
+
+    pub fn running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Whether anything is running in the background
+    pub fn busy(&self) -> bool {
+        self.sending() || self.running()
+    }
+
+    /// Runs the selected requests of the collection in the background and shows the report,
+    /// while a run is in progress its report is shown again
+    pub fn run_collection(&mut self, selection: Selection) {
+        if self.running() {
+            self.report.show();
+            return;
+        }
+        self.store_request();
+        let variables = match self.collection.environments.lock() {
+            Ok(environments) => environments.variables(),
+            Err(_) => vec![],
+        };
+        let client = self.client.clone();
+        // The clone shares the cookie jar with the collection
+        let collection = self.collection.clone();
+        let sender = self.run_sender.clone();
+        let name = match &selection.folder {
+            Some(folder) => format!("{}/{folder}", collection.name),
+            None => collection.name.clone(),
+        };
+        self.report
+            .start(&name, selection.requests(&collection).len());
+        self.run_id += 1;
+        let id = self.run_id;
+        let handle = tokio::spawn(async move {
+            let mut report =
+                runner::run_collection(&client, &collection, &selection, &variables, |result| {
+                    let _ = sender.send((id, RunMessage::Result(result.clone())));
+                })
                 .await;
-                match resp {
-                    Ok(r) => Ok(r),
-                    Err(_e) => Err(String::from("Invalid or unavailable URI (Make sure to include the url scheme, for example: http://)")),
+            // The report is titled with the folder that was run
+            report.collection = name;
+            let _ = sender.send((id, RunMessage::Finished(report)));
+        });
+        self.run = Some(Run { id, handle });
+    }
+
+    /// Runs the folder of the selected request with its subfolders, or the collection for
+    /// a request at the top level
+    pub fn run_folder(&mut self) {
+        let folder = self
+            .requests_list
+            .state
+            .selected()
+            .and_then(|i| self.collection.requests.get(i))
+            .map(|request| request.folder.clone())
+            .unwrap_or_default();
+        if folder.is_empty() {
+            self.run_collection(Selection::default());
+        } else {
+            self.run_collection(Selection::folder(&folder));
+        }
+    }
+
+    /// Stops running the collection, the results so far stay in the report
+    pub fn cancel_run(&mut self) {
+        if let Some(run) = self.run.take() {
+            run.handle.abort();
+            self.report.cancel();
+            // Cookies received so far are kept in the jar
+            self.save_collection();
+        }
+    }
+
+    /// Sends the request in the editor in the background, the response is picked up by
+    /// [`App::poll_responses`]
+    pub fn send_request(&mut self) {
+        if self.sending() {
+            return;
+        }
+        let variables = match self.collection.environments.lock() {
+            Ok(environments) => environments.variables(),
+            Err(_) => vec![],
+        };
+        let mut unresolved = vec![];
+        let request =
+            environment::interpolate_request(&self.current_request(), &variables, &mut unresolved);
+        if !unresolved.is_empty() {
+            self.right_state
+                .response_state
+                .set_response(format!("Unresolved variables: {}", unresolved.join(", ")));
+            return;
+        }
+        let history_request = request.clone();
+        let prepared = match self.collection.cookie_jar.lock() {
+            Ok(jar) => PreparedRequest::new(&request, Some(&jar)),
+            Err(_) => PreparedRequest::new(&request, None),
+        };
+        let prepared = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.right_state.response_state.set_response(e);
+                return;
+            }
+        };
+
+        self.request_id += 1;
+        let id = self.request_id;
+        let sender = self.response_sender.clone();
+        let client = self.client.clone();
+        let handle = tokio::spawn(async move {
+            let result = prepared.send(&client).await;
+            // The receiver only goes away when the app quits
+            let _ = sender.send((id, result));
+        });
+        let started = Instant::now();
+        self.in_flight = Some(InFlight {
+            id,
+            handle,
+            request: history_request,
+            sent: SystemTime::now(),
+        });
+        self.right_state.response_state.sending = Some(started);
+    }
+
+    /// Applies changes of the client settings in the config, the client is kept when they
+    /// did not change. Requests that are being sent keep the settings they started with.
+    pub fn reload_config(&mut self) {
+        let message = match Config::load(Config::path()) {
+            Ok(config) => match self.client.configure(config.client) {
+                Ok(_) => String::from("Reloaded the client settings"),
+                Err(e) => format!("Invalid client settings, kept the previous ones: {e}"),
+            },
+            Err(e) => format!("Failed to load config: {e}"),
+        };
+        self.right_state.response_state.set_response(message);
+    }
+
+    /// Aborts the request that is being sent
+    pub fn cancel_request(&mut self) {
+        if let Some(in_flight) = self.in_flight.take() {
+            in_flight.handle.abort();
+            self.right_state.response_state.sending = None;
+            self.right_state
+                .response_state
+                .set_response(String::from("Request cancelled"));
+        }
+    }
+
+    /// Handles responses of requests that finished since the last call
+    pub fn poll_responses(&mut self) {
+        while let Ok((id, message)) = self.run_receiver.try_recv() {
+            // Messages of cancelled runs are ignored
+            if self.run.as_ref().map(|run| run.id) != Some(id) {
+                continue;
+            }
+            match message {
+                RunMessage::Result(result) => self.report.add(result),
+                RunMessage::Finished(report) => {
+                    self.run = None;
+                    self.report.finish(report);
+                    // Cookies received during the run are kept in the jar
+                    self.save_collection();
+                }
+            }
+        }
+        while let Ok((id, result)) = self.response_receiver.try_recv() {
+            // Responses of cancelled requests are ignored
+            let in_flight = match self.in_flight.take() {
+                Some(in_flight) if in_flight.id == id => in_flight,
+                other => {
+                    self.in_flight = other;
+                    continue;
+                }
+            };
+            self.right_state.response_state.sending = None;
+            let extractions = in_flight.request.extractions.clone();
+            self.history_list.history.push(HistoryEntry::new(
+                in_flight.request,
+                in_flight.sent,
+                result.as_ref(),
+            ));
+            self.history_list.entry_added();
+            self.save_history();
+            match result {
+                Ok(response) => {
+                    let extracted = extract::extract(&extractions, &response);
+                    self.show_response(response);
+                    self.store_extracted(extracted);
+                }
+                Err(e) => self.right_state.response_state.set_response(e),
+            }
+        }
+    }
+
+    /// Sets values extracted from a response in the active environment
+    fn store_extracted(&mut self, extracted: Vec<(String, Result<String, String>)>) {
+        if extracted.is_empty() {
+            return;
+        }
+        let mut set = vec![];
+        let mut failed = vec![];
+        if let Ok(mut environments) = self.collection.environments.lock() {
+            let mut environment = environments.active_mut();
+            for (name, value) in extracted {
+                match (value, environment.as_mut()) {
+                    (Ok(value), Some(environment)) => {
+                        environment::set_variable(&mut environment.variables, &name, value);
+                        set.push(name);
+                    }
+                    (Ok(_), None) => failed.push(format!("{name} (no active environment)")),
+                    (Err(e), _) => failed.push(format!("{name} ({e})")),
                 }
             }
-            _ => panic!("Not a valid method?"),
+        }
+        let save = !set.is_empty();
+        let response_state = &mut self.right_state.response_state;
+        response_state.extracted = set;
+        response_state.extract_errors = failed;
+        if save {
+            self.save_collection();
+        }
+    }
+
+    fn show_response(&mut self, response: ReceivedResponse) {
+        let cookies = response.cookies;
+        self.display_response(
+            response.status,
+            response.time,
+            response.headers,
+            response.body,
+            None,
+        );
+        let response_state = &mut self.right_state.response_state;
+        response_state.reused = Some(response.reused);
+        response_state.redirects = response.redirects;
+        response_state.retries = response.retries;
+        response_state.timing = Some(response.timing);
+        self.store_cookies(cookies);
+    }
+
+    /// Shows a response in the response pane. The cookies are read from the headers when
+    /// `url` is given, otherwise they are left to the caller.
+    fn display_response(
+        &mut self,
+        status: StatusCode,
+        time: Duration,
+        headers: HeaderMap,
+        body: String,
+        url: Option<Url>,
+    ) {
+        let response_state = &mut self.right_state.response_state;
+        if let Some(url) = url {
+            response_state
+                .cookies
+                .set_received(cookies::from_response(&url, &headers));
+        }
+        response_state.time = time;
+        response_state.reused = None;
+        response_state.redirects.clear();
+        response_state.retries = 0;
+        response_state.timing = None;
+        response_state.status_code = status;
+        response_state.extracted.clear();
+        response_state.extract_errors.clear();
+        response_state.set_headers(headers);
+        match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(value) => response_state.set_json(value),
+            Err(_) => response_state.set_body(body),
         }
     }
 }
@@ -0,0 +1,185 @@
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::HeaderValue;
+
+use crate::{
+    collection::{BodyMode, KeyValue, SavedRequest},
+    query,
+};
+
+/// Content types that a raw body can be switched between, others can be set in the headers
+pub const RAW_CONTENT_TYPES: &[&str] = &[
+    "text/plain",
+    "application/xml",
+    "text/html",
+    "text/csv",
+    "application/javascript",
+    "application/x-yaml",
+];
+
+/// The bytes of a request body and their content type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedBody {
+    pub content_type: HeaderValue,
+    pub bytes: Vec<u8>,
+}
+
+/// Encodes the body of `request` as its body mode says, files are read from disk
+pub fn encode(request: &SavedRequest) -> Result<EncodedBody, String> {
+    let (content_type, bytes) = match request.body_mode {
+        BodyMode::Json => (
+            String::from("application/json"),
+            request.body.clone().into_bytes(),
+        ),
+        BodyMode::Raw => (
+            request.raw_content_type.clone(),
+            request.body.clone().into_bytes(),
+        ),
+        BodyMode::UrlEncoded => (
+            String::from("application/x-www-form-urlencoded"),
+            url_encoded(&request.form).into_bytes(),
+        ),
+        BodyMode::Multipart => {
+            let (boundary, bytes) = multipart(&request.form)?;
+            (format!("multipart/form-data; boundary={boundary}"), bytes)
+        }
+        BodyMode::Binary => (
+            String::from("application/octet-stream"),
+            read(&request.file)?,
+        ),
+    };
+    let content_type = HeaderValue::from_str(content_type.trim())
+        .map_err(|e| format!("Invalid content type '{content_type}': {e}"))?;
+    Ok(EncodedBody {
+        content_type,
+        bytes,
+    })
+}
+
+/// The enabled rows like the query of an url, `a=1&b=2`
+pub fn url_encoded(form: &[KeyValue]) -> String {
+    query::with_query("", form)
+        .trim_start_matches('?')
+        .to_string()
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    if path.trim().is_empty() {
+        return Err(String::from("No file selected for the body"));
+    }
+    fs::read(path.trim()).map_err(|e| format!("Failed to read '{path}': {e}"))
+}
+
+/// Builds a `multipart/form-data` body of the enabled rows, returns the boundary and the body
+fn multipart(form: &[KeyValue]) -> Result<(String, Vec<u8>), String> {
+    let mut parts = vec![];
+    for row in form.iter().filter(|row| row.enabled && !row.key.is_empty()) {
+        let name = row.key.replace('"', "%22");
+        let part = match row.file {
+            true => {
+                let path = row.value.trim();
+                let filename = Path::new(path)
+                    .file_name()
+                    .map(|f| f.to_string_lossy().replace('"', "%22"))
+                    .unwrap_or_default();
+                let mut part = format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+                )
+                .into_bytes();
+                part.extend(read(path)?);
+                part
+            }
+            false => format!(
+                "Content-Disposition: form-data; name=\"{name}\"\r\n\r\n{}",
+                row.value
+            )
+            .into_bytes(),
+        };
+        parts.push(part);
+    }
+
+    // The boundary must not occur in any of the parts
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos());
+    let mut boundary = format!("------------------------ruest{nanos:x}");
+    while parts.iter().any(|part| contains(part, boundary.as_bytes())) {
+        boundary.push('x');
+    }
+
+    let mut body = vec![];
+    for part in parts {
+        body.extend(format!("--{boundary}\r\n").into_bytes());
+        body.extend(part);
+        body.extend(b"\r\n");
+    }
+    body.extend(format!("--{boundary}--\r\n").into_bytes());
+    Ok((boundary, body))
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::collection::{BodyMode, KeyValue, SavedRequest};
+
+    #[test]
+    fn encode_body_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ruest-body-test.bin");
+        std::fs::write(&path, [0u8, 159, 146, 150]).unwrap();
+        let file = path.display().to_string();
+
+        let mut request = SavedRequest {
+            body_mode: BodyMode::UrlEncoded,
+            form: vec![KeyValue::new("q", "a b&c"), KeyValue::new("page", "2")],
+            ..Default::default()
+        };
+        let body = encode(&request).unwrap();
+        assert_eq!(body.content_type, "application/x-www-form-urlencoded");
+        assert_eq!(body.bytes, b"q=a%20b%26c&page=2");
+
+        request.body_mode = BodyMode::Multipart;
+        let upload = KeyValue {
+            file: true,
+            ..KeyValue::new("upload", &file)
+        };
+        request.form = vec![KeyValue::new("name", "it"), upload];
+        let body = encode(&request).unwrap();
+        let content_type = body.content_type.to_str().unwrap();
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let mut expected = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nit\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"ruest-body-test.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        )
+        .into_bytes();
+        expected.extend([0u8, 159, 146, 150]);
+        expected.extend(format!("\r\n--{boundary}--\r\n").into_bytes());
+        assert_eq!(body.bytes, expected);
+
+        request.body_mode = BodyMode::Binary;
+        request.file = file;
+        let body = encode(&request).unwrap();
+        assert_eq!(body.content_type, "application/octet-stream");
+        assert_eq!(body.bytes, [0u8, 159, 146, 150]);
+        request.file = String::from("/does/not/exist");
+        assert!(encode(&request).is_err());
+
+        request.body_mode = BodyMode::Raw;
+        request.raw_content_type = String::from("application/xml");
+        request.body = String::from("<a/>");
+        let body = encode(&request).unwrap();
+        assert_eq!(body.content_type, "application/xml");
+        assert_eq!(body.bytes, b"<a/>");
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::Write;
+
+use crate::{
+    client::HttpClient,
+    collection::Collection,
+    config::Config,
+    http::ReceivedResponse,
+    runner::{self, RunReport, Selection},
+};
+
+pub const USAGE: &str = "\
+Usage:
+  ruest-client                                  Start the interactive client
+  ruest-client run COLLECTION REQUEST [--env NAME]
+                                                Send a saved request and print the response
+  ruest-client test COLLECTION [REQUEST...] [--folder NAME] [--env NAME]
+                    [--format text|junit|json]  Send the requests of a collection, or of a folder
+                                                of it, in order and check their assertions
+  ruest-client help                             Show this message
+
+COLLECTION is the name of a collection in the data directory or a path to a collection file.
+The exit code of run is 0 for a successful (2xx) response and 1 for other responses,
+the exit code of test is 0 when all assertions passed and 1 otherwise. Errors exit with 2.";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Start the TUI
+    Interactive,
+    Run(RunOptions),
+    Test(TestOptions),
+    Help,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    pub collection: String,
+    pub request: String,
+    /// Environment to use instead of the active environment of the collection
+    pub env: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Junit,
+    Json,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TestOptions {
+    pub collection: String,
+    /// Names of the requests to run, all requests are run when empty
+    pub requests: Vec<String>,
+    /// Only run the requests in this folder and its subfolders
+    pub folder: Option<String>,
+    pub env: Option<String>,
+    pub format: ReportFormat,
+}
+
+/// Parses the command line arguments, without the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+        None => return Ok(Command::Interactive),
+        Some(command) => command,
+    };
+    match command.as_str() {
+        "run" | "test" => {
+            let mut positional = vec![];
+            let mut env = None;
+            let mut folder = None;
+            let mut format = ReportFormat::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--env" | "-e" => {
+                        env = Some(args.next().ok_or("Missing value for --env")?);
+                    }
+                    arg if arg.starts_with("--env=") => {
+                        env = Some(arg["--env=".len()..].to_string());
+                    }
+                    "--folder" if command == "test" => {
+                        folder = Some(args.next().ok_or("Missing value for --folder")?);
+                    }
+                    "--format" | "-f" if command == "test" => {
+                        format = match args.next().as_deref() {
+                            Some("text") => ReportFormat::Text,
+                            Some("junit") => ReportFormat::Junit,
+                            Some("json") => ReportFormat::Json,
+                            _ => return Err(String::from("Expected text, junit or json format")),
+                        };
+                    }
+                    arg if arg.starts_with('-') => return Err(format!("Unknown option '{arg}'")),
+                    _ => positional.push(arg),
+                }
+            }
+            if command == "test" {
+                let mut positional = positional.into_iter();
+                let collection = positional.next().ok_or("Expected a collection")?;
+                return Ok(Command::Test(TestOptions {
+                    collection,
+                    requests: positional.collect(),
+                    folder,
+                    env,
+                    format,
+                }));
+            }
+            match <[String; 2]>::try_from(positional) {
+                Ok([collection, request]) => Ok(Command::Run(RunOptions {
+                    collection,
+                    request,
+                    env,
+                })),
+                Err(_) => Err(String::from("Expected a collection and a request")),
+            }
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        command => Err(format!("Unknown command '{command}'")),
+    }
+}
+
+/// Loads an existing collection, unlike the interactive client a missing file is an error
+fn load(name: &str) -> Result<Collection, String> {
+    let path = Collection::path_for(name);
+    if !path.is_file() {
+        return Err(format!(
+            "Collection '{name}' not found at {}",
+            path.display()
+        ));
+    }
+    Collection::load(path).map_err(|e| format!("Failed to load collection: {e}"))
+}
+
+/// The HTTP client with the settings of the config
+fn client() -> Result<HttpClient, String> {
+    let config = Config::load(Config::path()).map_err(|e| format!("Failed to load config: {e}"))?;
+    HttpClient::new(config.client)
+}
+
+/// Saves the collection when its cookie jar may have changed
+fn save_cookies(collection: &Collection) -> Result<(), String> {
+    if collection.cookie_jar.lock().is_ok_and(|jar| jar.enabled) {
+        collection
+            .save()
+            .map_err(|e| format!("Failed to save collection: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Sends a saved request and writes the response to `out`.
+/// Returns whether the response status was successful.
+pub async fn run(options: &RunOptions, out: &mut impl Write) -> Result<bool, String> {
+    let collection = load(&options.collection)?;
+    let request = collection
+        .requests
+        .iter()
+        .find(|r| r.name == options.request)
+        .ok_or_else(|| {
+            format!(
+                "No request named '{}' in collection '{}', available requests: {}",
+                options.request,
+                collection.name,
+                collection.names().join(", ")
+            )
+        })?;
+    let variables = runner::variables(&collection, options.env.as_deref())?;
+    let response = runner::send(&client()?, &collection, request, &variables).await?;
+    // Cookies are kept in the jar, like they are in the interactive client
+    save_cookies(&collection)?;
+
+    print_response(&response, out).map_err(|e| format!("Failed to write response: {e}"))?;
+    Ok(response.status.is_success())
+}
+
+/// Runs the requests of a collection and writes a report to `out`.
+/// Returns whether all assertions passed.
+pub async fn test(options: &TestOptions, out: &mut impl Write) -> Result<bool, String> {
+    let collection = load(&options.collection)?;
+    if let Some(name) = options
+        .requests
+        .iter()
+        .find(|name| !collection.requests.iter().any(|r| &&r.name == name))
+    {
+        return Err(format!("No request named '{name}' in collection"));
+    }
+    let folders = collection.folders();
+    if let Some(folder) = options.folder.as_ref().filter(|f| !folders.contains(f)) {
+        return Err(format!(
+            "No folder named '{folder}' in collection, available folders: {}",
+            folders.join(", ")
+        ));
+    }
+    let selection = Selection {
+        names: options.requests.clone(),
+        folder: options.folder.clone(),
+    };
+    let variables = runner::variables(&collection, options.env.as_deref())?;
+    let client = client()?;
+    let report = runner::run_collection(&client, &collection, &selection, &variables, |_| ()).await;
+    save_cookies(&collection)?;
+
+    let output = match options.format {
+        ReportFormat::Text => report_text(&report),
+        ReportFormat::Junit => report.to_junit(),
+        ReportFormat::Json => report.to_json(),
+    };
+    writeln!(out, "{}", output.trim_end()).map_err(|e| format!("Failed to write report: {e}"))?;
+    Ok(report.failed == 0)
+}
+
+fn report_text(report: &RunReport) -> String {
+    let mut text = String::new();
+    for result in &report.results {
+        let mark = if result.passed() { "PASS" } else { "FAIL" };
+        let status = result.status.map_or(String::new(), |s| s.to_string());
+        text.push_str(&format!(
+            "{mark} {} {} {status} ({} ms)\n",
+            result.method, result.name, result.time_ms
+        ));
+        if let Some(error) = &result.error {
+            text.push_str(&format!("  {error}\n"));
+        }
+        for assertion in &result.assertions {
+            if let Some(error) = &assertion.error {
+                text.push_str(&format!("  {}: {error}\n", assertion.assertion));
+            }
+        }
+    }
+    text.push_str(&format!(
+        "\n{} passed, {} failed ({} ms)",
+        report.passed, report.failed, report.time_ms
+    ));
+    text
+}
+
+fn print_response(response: &ReceivedResponse, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{} ({} ms)",
+        response.status,
+        response.time.as_millis()
+    )?;
+    for (name, value) in &response.headers {
+        writeln!(out, "{name}: {}", String::from_utf8_lossy(value.as_bytes()))?;
+    }
+    writeln!(out)?;
+    writeln!(out, "{}", response.body)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+
+    use super::{parse, run, Command, RunOptions, TestOptions};
+    use crate::{
+        collection::{Collection, KeyValue, SavedRequest},
+        environment::Environment,
+    };
+
+    #[test]
+    fn parse_run() {
+        let args = ["run", "api", "Users", "--env", "staging"].map(String::from);
+        assert_eq!(
+            parse(args).unwrap(),
+            Command::Run(RunOptions {
+                collection: String::from("api"),
+                request: String::from("Users"),
+                env: Some(String::from("staging")),
+            })
+        );
+        assert_eq!(parse(Vec::new()).unwrap(), Command::Interactive);
+        assert!(parse(["run", "api"].map(String::from)).is_err());
+
+        let args = ["test", "api", "--folder", "users/admin"].map(String::from);
+        assert_eq!(
+            parse(args).unwrap(),
+            Command::Test(TestOptions {
+                collection: String::from("api"),
+                folder: Some(String::from("users/admin")),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn run_saved_request() {
+        let _mock = mock("GET", "/users")
+            .with_status(404)
+            .with_header("x-test", "1")
+            .with_body("missing")
+            .create();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        let mut options = RunOptions {
+            collection: path.to_string_lossy().into_owned(),
+            request: String::from("Users"),
+            env: None,
+        };
+        let mut out = vec![];
+        let error = run(&options, &mut out).await.unwrap_err();
+        assert!(error.starts_with("Collection '"), "{error}");
+
+        let mut collection = Collection::load(&path).unwrap();
+        collection.requests = vec![SavedRequest {
+            name: String::from("Users"),
+            url: String::from("{{host}}/users"),
+            body: String::new(),
+            ..Default::default()
+        }];
+        collection.environments.lock().unwrap().list = vec![Environment {
+            name: String::from("local"),
+            variables: vec![KeyValue::new("host", mockito::server_url())],
+        }];
+        collection.save().unwrap();
+
+        let error = run(&options, &mut out).await.unwrap_err();
+        assert_eq!(error, "Unresolved variables: host");
+
+        options.env = Some(String::from("local"));
+        let success = run(&options, &mut out).await.unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(!success);
+        assert!(out.starts_with("404 Not Found ("));
+        assert!(out.contains("x-test: 1\n"));
+        assert!(out.ends_with("\nmissing\n"));
+    }
+}
@@ -0,0 +1,390 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hyper::{Body, Request, Response};
+use reqwest::{
+    header::{HeaderValue, PROXY_AUTHORIZATION},
+    Method, StatusCode,
+};
+use serde::Deserialize;
+use tokio_native_tls::native_tls::TlsConnector;
+
+use crate::{
+    collection::KeyValue,
+    connector::{Connector, Proxies},
+};
+
+/// How long an unused connection stays in the pool
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+const SETTINGS: &str =
+    "expected timeout, connect_timeout, redirects, retries, retry_backoff, retry_status or \
+     retry_non_idempotent";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// HTTP/1.1, or HTTP/2 when the server offers it
+    #[default]
+    Auto,
+    Http1,
+    /// HTTP/2 without negotiating it first
+    Http2,
+}
+
+/// Settings of the HTTP client, the `client` object of the config
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientSettings {
+    /// Timeout of a whole request in milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Timeout of connecting in milliseconds
+    pub connect_timeout_ms: Option<u64>,
+    /// Maximum number of redirects that are followed, 0 disables following redirects
+    pub max_redirects: usize,
+    /// How often a request is sent again after a connection error or a status of
+    /// `retry_statuses`
+    pub retries: u32,
+    /// Milliseconds before the first retry, doubled for every following retry
+    pub retry_backoff_ms: u64,
+    pub retry_statuses: Vec<u16>,
+    /// Also retry requests like `POST` on `retry_statuses`, the server may have acted on them
+    /// already. They are always retried after a connection error.
+    pub retry_non_idempotent: bool,
+    /// Proxy for all requests, like `http://localhost:3128`. Without it the proxy of the
+    /// environment variables is used.
+    pub proxy: Option<String>,
+    /// Accept invalid TLS certificates, like self-signed ones
+    pub insecure: bool,
+    pub http_version: HttpVersion,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: None,
+            connect_timeout_ms: None,
+            max_redirects: 10,
+            retries: 0,
+            retry_backoff_ms: 500,
+            retry_statuses: vec![502, 503, 504],
+            retry_non_idempotent: false,
+            proxy: None,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+        }
+    }
+}
+
+impl ClientSettings {
+    /// Redirects are followed by [`crate::http`], which keeps the chain of redirects
+    fn build(&self, connect_timeout: Option<Duration>) -> Result<Pool, String> {
+        let proxies = Arc::new(Proxies::new(self.proxy.as_deref())?);
+        // HTTP/2 is offered to servers during the TLS handshake
+        let protocols: &[&str] = match self.http_version {
+            HttpVersion::Auto => &["h2", "http/1.1"],
+            HttpVersion::Http1 => &["http/1.1"],
+            HttpVersion::Http2 => &["h2"],
+        };
+        let tls = TlsConnector::builder()
+            .danger_accept_invalid_certs(self.insecure)
+            .request_alpns(protocols)
+            .build()
+            .map_err(|e| format!("Failed to create the HTTP client: {e}"))?;
+        let connector = Connector::new(tls.into(), proxies.clone(), connect_timeout);
+        let client = hyper::Client::builder()
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .http2_only(self.http_version == HttpVersion::Http2)
+            .build(connector);
+        Ok(Pool { client, proxies })
+    }
+
+    /// The settings that need new connections when they change
+    fn connections(&self) -> (&Option<String>, bool, HttpVersion) {
+        (&self.proxy, self.insecure, self.http_version)
+    }
+
+    /// Settings of a request, the enabled rows of its settings table override these settings
+    pub fn for_request(&self, rows: &[KeyValue]) -> Result<RequestSettings, String> {
+        let mut settings = RequestSettings::from(self);
+        for row in rows.iter().filter(|row| row.enabled && !row.key.is_empty()) {
+            settings
+                .set(row.key.trim(), row.value.trim())
+                .map_err(|e| format!("Invalid setting '{}': {e}", row.key))?;
+        }
+        Ok(settings)
+    }
+}
+
+/// How a request is sent. The settings table of a request is stored as key/value rows:
+///
+/// | key                    | value                                                   |
+/// |------------------------|---------------------------------------------------------|
+/// | `timeout`              | milliseconds for the whole request, or `none`           |
+/// | `connect_timeout`      | milliseconds for connecting, or `none`                  |
+/// | `redirects`            | maximum number of redirects, or `none` to not follow    |
+/// | `retries`              | how often a failed request is sent again                |
+/// | `retry_backoff`        | milliseconds before the first retry, doubled after each |
+/// | `retry_status`         | status codes that are retried, like `502, 503`          |
+/// | `retry_non_idempotent` | `true` to retry requests like `POST` on those codes     |
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestSettings {
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub max_redirects: usize,
+    pub retries: u32,
+    pub retry_backoff: Duration,
+    pub retry_statuses: Vec<u16>,
+    pub retry_non_idempotent: bool,
+}
+
+impl From<&ClientSettings> for RequestSettings {
+    fn from(settings: &ClientSettings) -> Self {
+        Self {
+            timeout: settings.timeout_ms.map(Duration::from_millis),
+            connect_timeout: settings.connect_timeout_ms.map(Duration::from_millis),
+            max_redirects: settings.max_redirects,
+            retries: settings.retries,
+            retry_backoff: Duration::from_millis(settings.retry_backoff_ms),
+            retry_statuses: settings.retry_statuses.clone(),
+            retry_non_idempotent: settings.retry_non_idempotent,
+        }
+    }
+}
+
+impl RequestSettings {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("expected a number, got '{value}'"))
+        };
+        let millis = |value: &str| match value {
+            "none" => Ok(None),
+            value => number(value).map(|ms| Some(Duration::from_millis(ms))),
+        };
+        match key {
+            "timeout" => self.timeout = millis(value)?,
+            "connect_timeout" => self.connect_timeout = millis(value)?,
+            "redirects" => {
+                self.max_redirects = match value {
+                    "none" => 0,
+                    value => number(value)? as usize,
+                }
+            }
+            "retries" => self.retries = number(value)?.min(u32::MAX as u64) as u32,
+            "retry_backoff" => self.retry_backoff = Duration::from_millis(number(value)?),
+            "retry_status" => {
+                self.retry_statuses = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|status| !status.is_empty())
+                    .map(|status| match status.parse::<u16>() {
+                        Ok(status @ 100..=599) => Ok(status),
+                        _ => Err(format!("expected a status code, got '{status}'")),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "retry_non_idempotent" => {
+                self.retry_non_idempotent = value
+                    .parse()
+                    .map_err(|_| format!("expected true or false, got '{value}'"))?
+            }
+            _ => return Err(String::from(SETTINGS)),
+        }
+        Ok(())
+    }
+
+    /// Whether a response with `status` to a request with `method` is sent again
+    pub fn retries_status(&self, method: &Method, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+            && (method.is_idempotent() || self.retry_non_idempotent)
+    }
+
+    /// How long to wait before retry `attempt`, starting at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+    }
+}
+
+/// Validator for the settings table
+pub fn validate_setting(row: &KeyValue) -> Option<String> {
+    RequestSettings::from(&ClientSettings::default())
+        .set(row.key.trim(), row.value.trim())
+        .err()
+}
+
+/// Long-lived HTTP client, its connections are kept open between requests.
+/// Clones share the client and its connection pools.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    settings: ClientSettings,
+    /// Pools by connect timeout, the connector of a pool has a single connect timeout
+    pools: Arc<Mutex<HashMap<Option<Duration>, Pool>>>,
+}
+
+impl HttpClient {
+    pub fn new(settings: ClientSettings) -> Result<Self, String> {
+        let connect_timeout = settings.connect_timeout_ms.map(Duration::from_millis);
+        let pool = settings.build(connect_timeout)?;
+        Ok(Self {
+            settings,
+            pools: Arc::new(Mutex::new(HashMap::from([(connect_timeout, pool)]))),
+        })
+    }
+
+    /// Applies `settings`, the client and its connections are only replaced when settings
+    /// of the connections changed. Returns whether the client was replaced.
+    pub fn configure(&mut self, settings: ClientSettings) -> Result<bool, String> {
+        if settings.connections() == self.settings.connections() {
+            self.settings = settings;
+            return Ok(false);
+        }
+        *self = Self::new(settings)?;
+        Ok(true)
+    }
+
+    pub fn settings(&self) -> &ClientSettings {
+        &self.settings
+    }
+
+    /// The pool of connections with the given connect timeout
+    pub fn pool(&self, connect_timeout: Option<Duration>) -> Result<Pool, String> {
+        let mut pools = self
+            .pools
+            .lock()
+            .map_err(|_| String::from("The HTTP client is unavailable"))?;
+        if let Some(pool) = pools.get(&connect_timeout) {
+            return Ok(pool.clone());
+        }
+        let pool = self.settings.build(connect_timeout)?;
+        pools.insert(connect_timeout, pool.clone());
+        Ok(pool)
+    }
+}
+
+/// Connections to the servers of requests, kept open to be reused
+#[derive(Debug, Clone)]
+pub struct Pool {
+    client: hyper::Client<Connector>,
+    proxies: Arc<Proxies>,
+}
+
+impl Pool {
+    /// Sends a request over a connection of the pool, or over a new one when none can be
+    /// reused. The credentials of the proxy are added to requests that are sent to it.
+    pub async fn send(&self, mut request: Request<Body>) -> hyper::Result<Response<Body>> {
+        let uri = request.uri();
+        // The proxy only sees the request that opens the tunnel of https requests
+        let authorization = match uri.scheme_str() {
+            Some("http") => self
+                .proxies
+                .get(false, uri.host().unwrap_or_default())
+                .and_then(|proxy| proxy.authorization.as_deref())
+                .and_then(|authorization| HeaderValue::from_str(authorization).ok()),
+            _ => None,
+        };
+        if let Some(authorization) = authorization {
+            request
+                .headers_mut()
+                .insert(PROXY_AUTHORIZATION, authorization);
+        }
+        self.client.request(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hyper::{Body, Request};
+    use mockito::mock;
+    use reqwest::{Method, StatusCode};
+
+    use super::{ClientSettings, HttpClient};
+    use crate::collection::KeyValue;
+
+    #[tokio::test]
+    async fn configure() {
+        let mut client = HttpClient::new(ClientSettings::default()).unwrap();
+        let url = format!("{}/client/configure", mockito::server_url());
+        let _mock = mock("GET", "/client/configure").create();
+        let get = || Request::get(&url).body(Body::empty()).unwrap();
+        let pool = client.pool(None).unwrap();
+        assert!(pool.send(get()).await.unwrap().status().is_success());
+        // Settings of requests do not need new connections
+        let settings = ClientSettings {
+            max_redirects: 0,
+            retries: 2,
+            ..Default::default()
+        };
+        assert!(!client.configure(settings).unwrap());
+        assert_eq!(client.settings().retries, 2);
+        // Another connect timeout has its own pool
+        let other = client.pool(Some(Duration::from_secs(1))).unwrap();
+        assert!(other.send(get()).await.unwrap().status().is_success());
+
+        let settings = ClientSettings {
+            insecure: true,
+            ..Default::default()
+        };
+        assert!(client.configure(settings).unwrap());
+
+        let settings = ClientSettings {
+            proxy: Some(String::from("not a proxy")),
+            ..Default::default()
+        };
+        assert!(client.configure(settings).is_err());
+    }
+
+    #[test]
+    fn request_settings() {
+        let global = ClientSettings {
+            timeout_ms: Some(1000),
+            ..Default::default()
+        };
+        let settings = global
+            .for_request(&[
+                KeyValue::new("connect_timeout", "200"),
+                KeyValue::new("redirects", "none"),
+                KeyValue::new("retries", "3"),
+                KeyValue::new("retry_status", "500, 429"),
+                KeyValue {
+                    enabled: false,
+                    ..KeyValue::new("timeout", "none")
+                },
+            ])
+            .unwrap();
+        assert_eq!(settings.timeout, Some(Duration::from_secs(1)));
+        assert_eq!(settings.connect_timeout, Some(Duration::from_millis(200)));
+        assert_eq!(settings.max_redirects, 0);
+        assert_eq!(settings.retry_statuses, [500, 429]);
+        assert_eq!(settings.backoff(1), Duration::from_millis(500));
+        assert_eq!(settings.backoff(3), Duration::from_millis(2000));
+        let server_error = StatusCode::INTERNAL_SERVER_ERROR;
+        assert!(settings.retries_status(&Method::PUT, server_error));
+        assert!(!settings.retries_status(&Method::POST, server_error));
+        assert!(!settings.retries_status(&Method::PUT, StatusCode::BAD_GATEWAY));
+        let settings = global
+            .for_request(&[KeyValue::new("retry_non_idempotent", "true")])
+            .unwrap();
+        assert!(settings.retries_status(&Method::POST, StatusCode::BAD_GATEWAY));
+
+        assert!(global
+            .for_request(&[KeyValue::new("timeout", "soon")])
+            .is_err());
+        assert!(global
+            .for_request(&[KeyValue::new("retry_status", "999")])
+            .is_err());
+        assert!(global
+            .for_request(&[KeyValue::new("proxy", "none")])
+            .is_err());
+        assert!(global
+            .for_request(&[KeyValue::new("retry_non_idempotent", "yes")])
+            .is_err());
+    }
+}
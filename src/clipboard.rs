@@ -0,0 +1,15 @@
+use cli_clipboard::{ClipboardContext, ClipboardProvider};
+
+/// Puts `text` into the system clipboard, which is missing on headless or remote systems
+pub fn copy(text: String) -> Result<(), String> {
+    ClipboardContext::new()
+        .and_then(|mut ctx| ctx.set_contents(text))
+        .map_err(|e| format!("Failed to copy to the clipboard: {e}"))
+}
+
+/// The text in the system clipboard
+pub fn paste() -> Result<String, String> {
+    ClipboardContext::new()
+        .and_then(|mut ctx| ctx.get_contents())
+        .map_err(|e| format!("Failed to read the clipboard: {e}"))
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config, cookies::CookieJar, environment::Environments};
+
+pub const DEFAULT_COLLECTION: &str = "default";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    /// Whether the value of a multipart form row is the path of a file to send
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub file: bool,
+}
+
+impl KeyValue {
+    /// An enabled row
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            enabled: true,
+            file: false,
+        }
+    }
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Auth {
+    #[default]
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    ApiKey {
+        key: String,
+        value: String,
+        location: ApiKeyLocation,
+    },
+}
+
+/// How the body of a request is sent, see [`crate::body::encode`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyMode {
+    /// The body text as `application/json`
+    #[default]
+    Json,
+    /// The body text with `raw_content_type`
+    Raw,
+    /// The form rows as `application/x-www-form-urlencoded`
+    UrlEncoded,
+    /// The form rows as `multipart/form-data`, with files for the rows marked as files
+    Multipart,
+    /// The contents of `file`
+    Binary,
+}
+
+/// A single request as it is stored in a collection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedRequest {
+    pub name: String,
+    /// Folder of the request, subfolders are separated by `/`, empty at the top level
+    #[serde(default)]
+    pub folder: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<KeyValue>,
+    #[serde(default)]
+    pub query: Vec<KeyValue>,
+    #[serde(default)]
+    pub auth: Auth,
+    #[serde(default)]
+    pub body_mode: BodyMode,
+    #[serde(default)]
+    pub body: String,
+    /// Content type of a raw body
+    #[serde(default = "raw_content_type_default")]
+    pub raw_content_type: String,
+    /// Fields of an url-encoded or multipart body
+    #[serde(default)]
+    pub form: Vec<KeyValue>,
+    /// Path of the file that is sent as a binary body
+    #[serde(default)]
+    pub file: String,
+    /// Checks on the response, see [`crate::runner::Assertion`]
+    #[serde(default)]
+    pub assertions: Vec<KeyValue>,
+    /// Variables that are set from the response, see [`crate::extract::Extraction`]
+    #[serde(default)]
+    pub extractions: Vec<KeyValue>,
+    /// How the request is sent, see [`crate::client::RequestSettings`]
+    #[serde(default)]
+    pub settings: Vec<KeyValue>,
+}
+
+impl Default for SavedRequest {
+    fn default() -> Self {
+        Self {
+            name: String::from("New request"),
+            folder: String::new(),
+            method: reqwest::Method::GET.to_string(),
+            url: String::new(),
+            headers: Vec::new(),
+            query: Vec::new(),
+            auth: Auth::None,
+            body_mode: BodyMode::Json,
+            body: String::from("{\n\n}"),
+            raw_content_type: raw_content_type_default(),
+            form: Vec::new(),
+            file: String::new(),
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+            settings: Vec::new(),
+        }
+    }
+}
+
+fn raw_content_type_default() -> String {
+    String::from("text/plain")
+}
+
+impl SavedRequest {
+    pub fn method(&self) -> reqwest::Method {
+        reqwest::Method::from_bytes(self.method.as_bytes()).unwrap_or(reqwest::Method::GET)
+    }
+
+    /// Whether the request is in `folder` or one of its subfolders
+    pub fn in_folder(&self, folder: &str) -> bool {
+        self.folder == folder
+            || self
+                .folder
+                .strip_prefix(folder)
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// A named list of requests, persisted as a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Collection {
+    #[serde(skip)]
+    path: PathBuf,
+    pub name: String,
+    #[serde(default)]
+    pub requests: Vec<SavedRequest>,
+    /// Cookies that are replayed on requests of this collection, when enabled
+    #[serde(default)]
+    pub cookie_jar: Arc<Mutex<CookieJar>>,
+    /// Variables that are substituted into the requests of this collection
+    #[serde(default)]
+    pub environments: Arc<Mutex<Environments>>,
+}
+
+impl Collection {
+    /// Path of a collection with the given name inside the data directory.
+    /// Names that point to an existing file are used as a path directly.
+    pub fn path_for(name: &str) -> PathBuf {
+        let path = Path::new(name);
+        if path.is_file() {
+            path.to_path_buf()
+        } else {
+            config::collections_dir().join(format!("{name}.json"))
+        }
+    }
+
+    /// Loads the collection at `path`, an empty collection is returned when the file does not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut collection = match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str::<Collection>(&data).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Collection {
+                name: path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| String::from(DEFAULT_COLLECTION)),
+                requests: vec![SavedRequest::default()],
+                ..Default::default()
+            },
+            Err(e) => return Err(e),
+        };
+        collection.path = path;
+        Ok(collection)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, data)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.requests.iter().map(|r| r.name.clone()).collect()
+    }
+
+    /// Every folder of the requests, with the folders that only contain subfolders
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders = BTreeSet::new();
+        for request in self.requests.iter().filter(|r| !r.folder.is_empty()) {
+            let mut end = 0;
+            for part in request.folder.split('/') {
+                end += part.len();
+                folders.insert(request.folder[..end].to_string());
+                end += 1;
+            }
+        }
+        folders.into_iter().collect()
+    }
+
+    /// Inserts a copy of the request at `index` right after it and returns the index of the copy
+    pub fn duplicate(&mut self, index: usize) -> Option<usize> {
+        let mut copy = self.requests.get(index)?.clone();
+        copy.name = format!("{} (copy)", copy.name);
+        self.requests.insert(index + 1, copy);
+        Some(index + 1)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<SavedRequest> {
+        if index < self.requests.len() {
+            Some(self.requests.remove(index))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collection, KeyValue, SavedRequest};
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        let mut collection = Collection::load(&path).unwrap();
+        collection.requests = vec![SavedRequest {
+            name: String::from("Users"),
+            method: String::from("POST"),
+            url: String::from("http://localhost/users"),
+            headers: vec![KeyValue::new("accept", "application/json")],
+            ..Default::default()
+        }];
+        collection.save().unwrap();
+
+        let loaded = Collection::load(&path).unwrap();
+        assert_eq!(loaded.requests, collection.requests);
+        assert_eq!(loaded.requests[0].method(), reqwest::Method::POST);
+    }
+}
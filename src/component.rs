@@ -2,12 +2,12 @@ use crossterm::event::KeyEvent;
 
 use crate::{
     app::{Action, InputMode},
-    keys::NormalKeyAction,
+    keys::{Binding, NormalKeyAction},
 };
 
 pub trait Component {
-    fn handle_key(&mut self, key_event: KeyEvent) -> Option<Action> {
-        NormalKeyAction::from(key_event).relative_or_none()
+    fn handle_key(&mut self, _key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        NormalKeyAction::from(binding).relative_or_none()
     }
 
     #[inline(always)]
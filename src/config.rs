@@ -0,0 +1,72 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{client::ClientSettings, keys::ConfigKeys, ui::theme::ConfigColor};
+
+/// Directory where collections and other persistent data are stored.
+/// Can be overridden with the `RUEST_DATA_DIR` environment variable.
+pub fn data_dir() -> PathBuf {
+    match std::env::var_os("RUEST_DATA_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ruest"),
+    }
+}
+
+pub fn collections_dir() -> PathBuf {
+    data_dir().join("collections")
+}
+
+/// Directory of the config file.
+/// Can be overridden with the `RUEST_CONFIG_DIR` environment variable.
+pub fn config_dir() -> PathBuf {
+    match std::env::var_os("RUEST_CONFIG_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ruest"),
+    }
+}
+
+/// Settings of the user, read from `config.json` in the config directory
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Keys of actions by the name of the action, replacing the default keys
+    pub keys: BTreeMap<String, ConfigKeys>,
+    /// Name of the theme that is used at startup
+    pub theme: Option<String>,
+    /// Colours of user themes by the name of the theme
+    pub themes: BTreeMap<String, BTreeMap<String, ConfigColor>>,
+    /// Whether the mouse can click and scroll, which disables selecting text in the terminal
+    pub mouse: bool,
+    /// Settings of the HTTP client
+    pub client: ClientSettings,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        config_dir().join("config.json")
+    }
+
+    /// Reads the config, a missing file is an empty config
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
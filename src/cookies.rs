@@ -0,0 +1,249 @@
+use std::{
+    cmp::Reverse,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    header::{HeaderMap, SET_COOKIE},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+
+/// A cookie as sent by a server in a `Set-Cookie` header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Only send the cookie to exactly `domain`, not to its subdomains
+    #[serde(default)]
+    pub host_only: bool,
+    /// Expiry as seconds since the unix epoch, `None` for session cookies
+    #[serde(default)]
+    pub expires: Option<u64>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub same_site: Option<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl Cookie {
+    /// Parses a `Set-Cookie` header value received in a response to `url`
+    pub fn parse(header: &str, url: &Url) -> Option<Self> {
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            path: default_path(url),
+            host_only: true,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+        let mut max_age = None;
+
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "expires" => {
+                    cookie.expires = httpdate::parse_http_date(value)
+                        .ok()
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs());
+                }
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "domain" if !value.is_empty() => {
+                    cookie.domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => cookie.same_site = Some(value.to_string()),
+                _ => (),
+            }
+        }
+        // Max-Age takes precedence over Expires
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(now().saturating_add_signed(max_age.max(-1)));
+        }
+
+        // Servers may not set cookies for unrelated domains
+        if !cookie.host_only && !domain_matches(&host, &cookie.domain) {
+            return None;
+        }
+        Some(cookie)
+    }
+
+    pub fn expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= now())
+    }
+
+    /// Whether this cookie should be sent along with a request to `url`
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return false,
+        };
+        let domain = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.expired()
+    }
+
+    /// Human readable expiry date
+    pub fn expires_display(&self) -> String {
+        match self.expires {
+            Some(secs) => httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_secs(secs)),
+            None => String::from("Session"),
+        }
+    }
+
+    /// Short description of the flags that are set
+    pub fn flags(&self) -> String {
+        let mut flags = vec![];
+        if self.secure {
+            flags.push(String::from("Secure"));
+        }
+        if self.http_only {
+            flags.push(String::from("HttpOnly"));
+        }
+        if let Some(same_site) = &self.same_site {
+            flags.push(format!("SameSite={same_site}"));
+        }
+        flags.join(" ")
+    }
+}
+
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => String::from("/"),
+        Some(i) => url.path()[..i].to_string(),
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+/// All cookies set by a response to `url`
+pub fn from_response(url: &Url, headers: &HeaderMap) -> Vec<Cookie> {
+    headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| Cookie::parse(value, url))
+        .collect()
+}
+
+/// Cookies that are stored between requests of a collection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CookieJar {
+    pub enabled: bool,
+    #[serde(default)]
+    pub cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Stores cookies from a response, replacing cookies with the same name, domain and path.
+    /// Expired cookies are removed from the jar.
+    pub fn store(&mut self, cookies: &[Cookie]) {
+        for cookie in cookies {
+            self.cookies.retain(|c| {
+                !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+            });
+            self.cookies.push(cookie.clone());
+        }
+        self.cookies.retain(|c| !c.expired());
+    }
+
+    /// Value for the `Cookie` header of a request to `url`
+    pub fn header_for(&self, url: &Url) -> Option<String> {
+        let mut cookies: Vec<_> = self.cookies.iter().filter(|c| c.matches(url)).collect();
+        // Cookies with longer paths are listed first
+        cookies.sort_by_key(|c| Reverse(c.path.len()));
+        let header = cookies
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use super::{Cookie, CookieJar};
+
+    #[test]
+    fn parse_attributes() {
+        let url = Url::parse("https://api.example.com/v1/login").unwrap();
+        let cookie = Cookie::parse(
+            "session=abc; Domain=.example.com; Path=/; Expires=Wed, 21 Oct 2065 07:28:00 GMT; Secure; HttpOnly; SameSite=Lax",
+            &url,
+        )
+        .unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only && cookie.secure && cookie.http_only);
+        assert_eq!(cookie.expires_display(), "Wed, 21 Oct 2065 07:28:00 GMT");
+        assert_eq!(cookie.flags(), "Secure HttpOnly SameSite=Lax");
+
+        assert!(Cookie::parse("a=b; Domain=other.com", &url).is_none());
+        assert_eq!(Cookie::parse("a=b", &url).unwrap().path, "/v1");
+    }
+
+    #[test]
+    fn jar_replays_matching_cookies() {
+        let login = Url::parse("http://example.com/auth/login").unwrap();
+        let mut jar = CookieJar::default();
+        jar.store(&[
+            Cookie::parse("token=1; Path=/", &login).unwrap(),
+            Cookie::parse("scoped=2", &login).unwrap(),
+            Cookie::parse("gone=3; Max-Age=0", &login).unwrap(),
+        ]);
+        assert_eq!(jar.cookies.len(), 2);
+
+        let other = Url::parse("http://example.com/users").unwrap();
+        assert_eq!(jar.header_for(&other).unwrap(), "token=1");
+        let auth = Url::parse("http://example.com/auth/me").unwrap();
+        assert_eq!(jar.header_for(&auth).unwrap(), "scoped=2; token=1");
+        let sub = Url::parse("http://www.example.com/").unwrap();
+        assert_eq!(jar.header_for(&sub), None);
+    }
+}
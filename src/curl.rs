@@ -0,0 +1,503 @@
+use std::fs;
+
+use reqwest::Method;
+
+use crate::{
+    body,
+    collection::{ApiKeyLocation, Auth, BodyMode, KeyValue, SavedRequest},
+    query,
+};
+
+/// Splits a command line into arguments like a POSIX shell would,
+/// supporting quotes, backslash escapes, `$'...'` strings and line continuations
+fn split_args(command: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => (),
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => current.get_or_insert_with(String::new).push(c),
+                None => return Err(String::from("Unexpected end after '\\'")),
+            },
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(String::from("Unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => arg.push(c),
+                            Some('\n') => (),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(String::from("Unterminated double quote")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(String::from("Unterminated double quote")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => arg.push('\n'),
+                            Some('r') => arg.push('\r'),
+                            Some('t') => arg.push('\t'),
+                            Some(c) => arg.push(c),
+                            None => return Err(String::from("Unterminated $'' string")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(String::from("Unterminated $'' string")),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Short options that take a value, which may be attached as in `-XPOST`
+const SHORT_WITH_VALUE: &str = "XHdubFAeomwTrEK";
+
+/// Long options that take a value but have no effect on the imported request
+const IGNORED_WITH_VALUE: &[&str] = &[
+    "--output",
+    "--max-time",
+    "--connect-timeout",
+    "--write-out",
+    "--retry",
+    "--proxy",
+    "--cert",
+    "--key",
+    "--cacert",
+    "--resolve",
+    "--upload-file",
+    "--config",
+    "--range",
+];
+
+/// Reads `@file` arguments of data options
+fn data_value(value: &str) -> Result<String, String> {
+    match value.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("Failed to read '{path}': {e}")),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Turns a `-F name=value` field into a row of a multipart body, `@file` parts become file
+/// rows, `literal` fields of `--form-string` are taken as they are
+fn form_row(field: &str, literal: bool) -> Result<KeyValue, String> {
+    let (name, value) = field
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid form field '{field}'"))?;
+    if literal {
+        return Ok(KeyValue::new(name, value));
+    }
+    if let Some(path) = value.strip_prefix('@') {
+        // Options like `;type=image/png` are left out
+        return Ok(KeyValue {
+            file: true,
+            ..KeyValue::new(name, path.split(';').next().unwrap_or(path))
+        });
+    }
+    let value = match value.strip_prefix('<') {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|e| format!("Failed to read '{path}': {e}"))?
+        }
+        None => value.to_string(),
+    };
+    Ok(KeyValue::new(name, value))
+}
+
+/// Parses a curl command into a request
+pub fn parse(command: &str) -> Result<SavedRequest, String> {
+    let mut args = split_args(command.trim())?.into_iter();
+    let mut request = SavedRequest {
+        body: String::new(),
+        ..Default::default()
+    };
+    let mut method = None;
+    let mut url = None;
+    let mut data = vec![];
+    let mut form = vec![];
+    let mut get = false;
+
+    let mut first = true;
+    while let Some(arg) = args.next() {
+        if std::mem::take(&mut first) && arg == "curl" {
+            continue;
+        }
+        // Split attached values of short options, like `-XPOST`
+        let (option, attached) = match arg.strip_prefix('-') {
+            Some(rest) if !rest.starts_with('-') && rest.len() > 1 && rest.is_char_boundary(1) => {
+                let (option, value) = rest.split_at(1);
+                if SHORT_WITH_VALUE.contains(option) {
+                    (format!("-{option}"), Some(value.to_string()))
+                } else {
+                    (arg.clone(), None)
+                }
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = || {
+            attached
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {option}"))
+        };
+
+        match option.as_str() {
+            "-X" | "--request" => method = Some(value()?),
+            "-H" | "--header" => {
+                let value = value()?;
+                let (key, value) = value.split_once(':').unwrap_or((&value, ""));
+                request
+                    .headers
+                    .push(KeyValue::new(key.trim(), value.trim()));
+            }
+            "-d" | "--data" | "--data-ascii" | "--data-binary" => {
+                let value = value()?;
+                data.push(data_value(&value)?);
+            }
+            "--data-raw" => data.push(value()?),
+            "--data-urlencode" => {
+                let value = value()?;
+                let encoded = match value.split_once('=') {
+                    Some((name, content)) => query::with_query("", &[KeyValue::new(name, content)]),
+                    None => query::with_query("", &[KeyValue::new(&value, "")]),
+                };
+                data.push(encoded.trim_start_matches('?').to_string());
+            }
+            "--json" => {
+                data.push(data_value(&value()?)?);
+                request
+                    .headers
+                    .push(KeyValue::new("Content-Type", "application/json"));
+                request
+                    .headers
+                    .push(KeyValue::new("Accept", "application/json"));
+            }
+            "-F" | "--form" => form.push(form_row(&value()?, false)?),
+            "--form-string" => form.push(form_row(&value()?, true)?),
+            "-u" | "--user" => {
+                let value = value()?;
+                let (username, password) = value.split_once(':').unwrap_or((&value, ""));
+                request.auth = Auth::Basic {
+                    username: username.to_string(),
+                    password: password.to_string(),
+                };
+            }
+            "-A" | "--user-agent" => request.headers.push(KeyValue::new("User-Agent", value()?)),
+            "-e" | "--referer" => request.headers.push(KeyValue::new("Referer", value()?)),
+            "-b" | "--cookie" => request.headers.push(KeyValue::new("Cookie", value()?)),
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => method = Some(Method::HEAD.to_string()),
+            "--url" => url = Some(value()?),
+            "-o" | "-m" | "-w" | "-T" | "-r" | "-E" | "-K" => {
+                value()?;
+            }
+            option if IGNORED_WITH_VALUE.contains(&option) => {
+                value()?;
+            }
+            // Flags like --compressed, -L, -s or -k do not change the request
+            option if option.starts_with('-') && option.len() > 1 => (),
+            _ if url.is_none() => url = Some(arg),
+            _ => return Err(format!("Unexpected argument '{arg}'")),
+        }
+    }
+
+    let url = url.ok_or_else(|| String::from("No url in curl command"))?;
+
+    if get {
+        // `-G` sends the data as query parameters
+        let mut params = query::parse(&url);
+        params.extend(query::parse(&format!("?{}", data.join("&"))));
+        request.url = query::with_query(&url, &params);
+    } else {
+        request.url = url;
+        if !form.is_empty() {
+            request.body_mode = BodyMode::Multipart;
+            request.form = form;
+        } else if !data.is_empty() {
+            data_body(&mut request, data.join("&"));
+        }
+    }
+
+    let has_body = !request.body.is_empty() || !request.form.is_empty();
+    request.method = match method {
+        Some(method) => method.to_uppercase(),
+        None if has_body => Method::POST.to_string(),
+        None => Method::GET.to_string(),
+    };
+    request.query = query::parse(&request.url);
+    request.name = request_name(&request);
+    Ok(request)
+}
+
+/// Puts the data of `-d` options into the body with the mode of its `Content-Type` header,
+/// which the mode then sets. Without a header curl sends the data url-encoded, it becomes form
+/// rows when they encode to the same data.
+fn data_body(request: &mut SavedRequest, data: String) {
+    let content_type = request
+        .headers
+        .iter()
+        .position(|h| h.key.eq_ignore_ascii_case("Content-Type"))
+        .map(|i| request.headers.remove(i).value)
+        .unwrap_or_else(|| String::from("application/x-www-form-urlencoded"));
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    if essence.eq_ignore_ascii_case("application/json") {
+        request.body_mode = BodyMode::Json;
+        request.body = data;
+        return;
+    }
+    if essence.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+        let form = query::parse(&format!("?{data}"));
+        if body::url_encoded(&form) == data {
+            request.body_mode = BodyMode::UrlEncoded;
+            request.form = form;
+            return;
+        }
+    }
+    request.body_mode = BodyMode::Raw;
+    request.raw_content_type = content_type;
+    request.body = data;
+}
+
+/// Name for an imported request, like `GET /users`
+fn request_name(request: &SavedRequest) -> String {
+    let path = reqwest::Url::parse(&request.url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| request.url.clone());
+    format!("{} {path}", request.method)
+}
+
+/// Quotes an argument for a POSIX shell
+fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Formats a request as a curl command
+pub fn export(request: &SavedRequest) -> String {
+    let method = request.method();
+    let mut url = request.url.clone();
+    let mut options = vec![];
+    let mut headers: Vec<_> = request
+        .headers
+        .iter()
+        .filter(|h| h.enabled && !h.key.is_empty())
+        .cloned()
+        .collect();
+    match &request.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            options.push(format!("-u {}", quote(&format!("{username}:{password}"))))
+        }
+        Auth::Bearer { token } => {
+            headers.push(KeyValue::new("Authorization", format!("Bearer {token}")))
+        }
+        Auth::ApiKey {
+            key,
+            value,
+            location: ApiKeyLocation::Header,
+        } => headers.push(KeyValue::new(key, value)),
+        Auth::ApiKey {
+            key,
+            value,
+            location: ApiKeyLocation::Query,
+        } => {
+            let mut params = query::parse(&url);
+            params.push(KeyValue::new(key, value));
+            url = query::with_query(&url, &params);
+        }
+    }
+
+    // Bodies of requests that normally have none, like GET, are left out
+    let has_body = match request.body_mode {
+        BodyMode::Json | BodyMode::Raw => !request.body.trim().is_empty(),
+        BodyMode::UrlEncoded | BodyMode::Multipart => request
+            .form
+            .iter()
+            .any(|row| row.enabled && !row.key.is_empty()),
+        BodyMode::Binary => !request.file.trim().is_empty(),
+    };
+    let send_body = has_body && method != Method::GET && method != Method::HEAD;
+    let has_content_type = headers
+        .iter()
+        .any(|h| h.key.eq_ignore_ascii_case("Content-Type"));
+    // curl sets the content type of multipart bodies itself, with the boundary
+    let content_type = match request.body_mode {
+        BodyMode::Json => Some("application/json"),
+        BodyMode::Raw => Some(request.raw_content_type.as_str()),
+        BodyMode::UrlEncoded => Some("application/x-www-form-urlencoded"),
+        BodyMode::Multipart => None,
+        BodyMode::Binary => Some("application/octet-stream"),
+    };
+    if let Some(content_type) = content_type.filter(|_| send_body && !has_content_type) {
+        headers.push(KeyValue::new("Content-Type", content_type));
+    }
+    for h in &headers {
+        options.push(format!("-H {}", quote(&format!("{}: {}", h.key, h.value))));
+    }
+    if send_body {
+        match request.body_mode {
+            BodyMode::Json | BodyMode::Raw => {
+                options.push(format!("--data-raw {}", quote(&request.body)))
+            }
+            BodyMode::UrlEncoded => options.push(format!(
+                "--data-raw {}",
+                quote(&body::url_encoded(&request.form))
+            )),
+            BodyMode::Multipart => {
+                for row in request
+                    .form
+                    .iter()
+                    .filter(|r| r.enabled && !r.key.is_empty())
+                {
+                    let option = match row.file {
+                        true => format!("-F {}", quote(&format!("{}=@{}", row.key, row.value))),
+                        false => format!(
+                            "--form-string {}",
+                            quote(&format!("{}={}", row.key, row.value))
+                        ),
+                    };
+                    options.push(option);
+                }
+            }
+            BodyMode::Binary => options.push(format!(
+                "--data-binary {}",
+                quote(&format!("@{}", request.file.trim()))
+            )),
+        }
+    }
+
+    let mut command = String::from("curl");
+    if method != Method::GET {
+        command.push_str(&format!(" -X {}", quote(method.as_str())));
+    }
+    command.push(' ');
+    command.push_str(&quote(&url));
+    for option in options {
+        command.push_str(" \\\n  ");
+        command.push_str(&option);
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, parse};
+    use crate::collection::{Auth, BodyMode};
+
+    #[test]
+    fn parse_options() {
+        let request = parse(
+            r#"curl 'https://api.example.com/users?page=2' \
+  -XPUT -H 'Accept: application/json' -H "X-Name: it's" \
+  -u admin:secret --compressed --data-raw $'{"a":\n1}'"#,
+        )
+        .unwrap();
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.url, "https://api.example.com/users?page=2");
+        assert_eq!(request.query[0].value, "2");
+        assert_eq!(request.headers[1].value, "it's");
+        assert_eq!(request.headers.len(), 2);
+        // Data that are not form fields are sent as they are, with the content type of curl
+        assert_eq!(request.body_mode, BodyMode::Raw);
+        assert_eq!(
+            request.raw_content_type,
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(request.body, "{\"a\":\n1}");
+        assert_eq!(
+            request.auth,
+            Auth::Basic {
+                username: String::from("admin"),
+                password: String::from("secret")
+            }
+        );
+
+        let form = parse("curl http://localhost/login -d user=it -d 'pass=a%20b'").unwrap();
+        assert_eq!(form.method, "POST");
+        assert_eq!(form.body_mode, BodyMode::UrlEncoded);
+        assert_eq!(form.form[1].value, "a b");
+        let json = parse("curl localhost -H 'content-type: application/json' -d '[1]'").unwrap();
+        assert_eq!(
+            (json.body_mode, json.body.as_str()),
+            (BodyMode::Json, "[1]")
+        );
+        assert!(json.headers.is_empty());
+
+        let get = parse("curl -G http://localhost/search -d q=rust -d page=1").unwrap();
+        assert_eq!(get.method, "GET");
+        assert_eq!(get.url, "http://localhost/search?q=rust&page=1");
+        assert!(parse("curl -H 'unterminated").is_err());
+    }
+
+    #[test]
+    fn export_roundtrip() {
+        let request = parse(
+            r#"curl -X POST http://localhost:8080/it --json '{"name": "it'\''s"}' -u 'a b:c'"#,
+        )
+        .unwrap();
+        let command = export(&request);
+        assert_eq!(
+            command,
+            "curl -X POST http://localhost:8080/it \\\n  -u 'a b:c' \\\n  -H 'Accept: application/json' \\\n  -H 'Content-Type: application/json' \\\n  --data-raw '{\"name\": \"it'\\''s\"}'"
+        );
+        let parsed = parse(&command).unwrap();
+        assert_eq!(parsed.body, request.body);
+        assert_eq!(parsed.headers, request.headers);
+        assert_eq!(parsed.auth, request.auth);
+
+        let form = parse(
+            "curl http://localhost/upload -F name=it --form-string 'note=<b>' -F 'photo=@/tmp/a.png;type=image/png'",
+        )
+        .unwrap();
+        assert_eq!(form.method, "POST");
+        assert_eq!(form.body_mode, BodyMode::Multipart);
+        assert_eq!(form.form[1].value, "<b>");
+        assert_eq!(form.form[2].value, "/tmp/a.png");
+        assert_eq!(
+            form.form.iter().map(|row| row.file).collect::<Vec<_>>(),
+            [false, false, true]
+        );
+        assert_eq!(parse(&export(&form)).unwrap().form, form.form);
+    }
+}
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::collection::{Auth, KeyValue, SavedRequest};
+
+/// A named set of variables that can be used as `{{name}}` in requests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Environment {
+    pub name: String,
+    #[serde(default)]
+    pub variables: Vec<KeyValue>,
+}
+
+impl Environment {
+    /// Variables that are enabled
+    pub fn variables(&self) -> Vec<KeyValue> {
+        self.variables
+            .iter()
+            .filter(|v| v.enabled)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Environments {
+    /// Name of the active environment
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub list: Vec<Environment>,
+}
+
+impl Environments {
+    pub fn active(&self) -> Option<&Environment> {
+        let name = self.active.as_ref()?;
+        self.list.iter().find(|env| &env.name == name)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Environment> {
+        let name = self.active.clone()?;
+        self.find_mut(&name)
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Environment> {
+        self.list.iter_mut().find(|env| env.name == name)
+    }
+
+    /// Enabled variables of the active environment
+    pub fn variables(&self) -> Vec<KeyValue> {
+        self.active()
+            .map(|env| {
+                env.variables
+                    .iter()
+                    .filter(|v| v.enabled)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Sets the value of the last variable named `name`, enabling it, or adds the variable
+pub fn set_variable(variables: &mut Vec<KeyValue>, name: &str, value: String) {
+    match variables.iter_mut().rev().find(|v| v.key == name) {
+        Some(variable) => {
+            variable.value = value;
+            variable.enabled = true;
+        }
+        None => variables.push(KeyValue::new(name, value)),
+    }
+}
+
+/// Names of all `{{name}}` placeholders in `text`, with the byte range of the placeholder
+pub fn placeholders(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = vec![];
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("{{").map(|i| i + offset) {
+        match text[start + 2..].find("}}") {
+            Some(len) => {
+                let end = start + 2 + len + 2;
+                found.push((start..end, text[start + 2..end - 2].trim()));
+                offset = end;
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+/// Replaces the placeholders in `text` with the value of the variable of the same name.
+/// Names of variables that are not defined are added to `unresolved`.
+pub fn interpolate(text: &str, variables: &[KeyValue], unresolved: &mut Vec<String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for (range, name) in placeholders(text) {
+        result.push_str(&text[last..range.start]);
+        // Later definitions of a variable take precedence
+        match variables.iter().rev().find(|v| v.key == name) {
+            Some(variable) => result.push_str(&variable.value),
+            None => {
+                if !unresolved.iter().any(|u| u == name) {
+                    unresolved.push(name.to_string());
+                }
+                result.push_str(&text[range.clone()]);
+            }
+        }
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// Replaces every placeholder with a neutral value, so the text can be validated before
+/// the variables are known
+pub fn strip_placeholders(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for (range, _) in placeholders(text) {
+        result.push_str(&text[last..range.start]);
+        result.push('x');
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// Names of the variables used in `request` that are not defined in `variables`
+pub fn unresolved(request: &SavedRequest, variables: &[KeyValue]) -> Vec<String> {
+    let mut unresolved = vec![];
+    interpolate_request(request, variables, &mut unresolved);
+    unresolved
+}
+
+/// Substitutes the variables in every part of a request
+pub fn interpolate_request(
+    request: &SavedRequest,
+    variables: &[KeyValue],
+    unresolved: &mut Vec<String>,
+) -> SavedRequest {
+    let mut text = |s: &String| interpolate(s, variables, unresolved);
+    let rows = |rows: &[KeyValue], text: &mut dyn FnMut(&String) -> String| {
+        rows.iter()
+            .filter(|row| row.enabled)
+            .map(|row| KeyValue {
+                file: row.file,
+                ..KeyValue::new(text(&row.key), text(&row.value))
+            })
+            .collect::<Vec<_>>()
+    };
+    let headers = rows(&request.headers, &mut text);
+    let query = rows(&request.query, &mut text);
+    let auth = match &request.auth {
+        Auth::None => Auth::None,
+        Auth::Basic { username, password } => Auth::Basic {
+            username: text(username),
+            password: text(password),
+        },
+        Auth::Bearer { token } => Auth::Bearer { token: text(token) },
+        Auth::ApiKey {
+            key,
+            value,
+            location,
+        } => Auth::ApiKey {
+            key: text(key),
+            value: text(value),
+            location: *location,
+        },
+    };
+    SavedRequest {
+        name: request.name.clone(),
+        folder: request.folder.clone(),
+        method: request.method.clone(),
+        url: text(&request.url),
+        headers,
+        query,
+        auth,
+        body_mode: request.body_mode,
+        body: text(&request.body),
+        raw_content_type: text(&request.raw_content_type),
+        form: rows(&request.form, &mut text),
+        file: text(&request.file),
+        assertions: request.assertions.clone(),
+        extractions: request.extractions.clone(),
+        settings: request.settings.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, strip_placeholders};
+    use crate::collection::KeyValue;
+
+    #[test]
+    fn interpolate_reports_unresolved() {
+        let variables = [KeyValue::new("host", "http://localhost:8080")];
+        let mut unresolved = vec![];
+        let url = interpolate(
+            "{{host}}/users/{{ id }}?token={{token}}&again={{id}}",
+            &variables,
+            &mut unresolved,
+        );
+        assert_eq!(
+            url,
+            "http://localhost:8080/users/{{ id }}?token={{token}}&again={{id}}"
+        );
+        assert_eq!(unresolved, ["id", "token"]);
+        assert_eq!(strip_placeholders("x-{{name}}-y {{"), "x-x-y {{");
+    }
+}
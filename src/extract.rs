@@ -0,0 +1,159 @@
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{collection::KeyValue, cookies, http::ReceivedResponse, runner::json_pointer};
+
+const EXPECTED: &str = "expected json PATH, header NAME, cookie NAME or regex PATTERN";
+
+/// A rule that reads a value from a response. Extractions are stored as key/value rows,
+/// the key is the variable that is set and the value is one of:
+///
+/// | rule            | value                                                  |
+/// |-----------------|--------------------------------------------------------|
+/// | `json PATH`     | value at the JSON path, strings without quotes         |
+/// | `header NAME`   | value of the response header                           |
+/// | `cookie NAME`   | value of a cookie set by the response                  |
+/// | `regex PATTERN` | first capture group, or the whole match, in the body   |
+#[derive(Debug, Clone)]
+pub enum Extraction {
+    Json(String),
+    Header(String),
+    Cookie(String),
+    Regex(Regex),
+}
+
+impl Extraction {
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let (kind, argument) = rule.trim().split_once(' ').unwrap_or((rule.trim(), ""));
+        let argument = argument.trim();
+        if argument.is_empty() {
+            return Err(String::from(EXPECTED));
+        }
+        match kind {
+            "json" => Ok(Self::Json(json_pointer(argument)?)),
+            "header" => Ok(Self::Header(argument.to_string())),
+            "cookie" => Ok(Self::Cookie(argument.to_string())),
+            "regex" => Regex::new(argument)
+                .map(Self::Regex)
+                .map_err(|e| format!("invalid regex: {e}")),
+            _ => Err(String::from(EXPECTED)),
+        }
+    }
+
+    pub fn extract(&self, response: &ReceivedResponse) -> Result<String, String> {
+        match self {
+            Self::Json(pointer) => {
+                let body: Value = serde_json::from_str(&response.body)
+                    .map_err(|_| String::from("body is not JSON"))?;
+                match body.pointer(pointer) {
+                    Some(Value::String(value)) => Ok(value.clone()),
+                    Some(value) => Ok(value.to_string()),
+                    None => Err(String::from("path not found")),
+                }
+            }
+            Self::Header(name) => response
+                .headers
+                .get(name.as_str())
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                .ok_or_else(|| String::from("header is missing")),
+            Self::Cookie(name) => cookies::from_response(&response.url, &response.headers)
+                .into_iter()
+                .rev()
+                .find(|cookie| &cookie.name == name)
+                .map(|cookie| cookie.value)
+                .ok_or_else(|| String::from("cookie is missing")),
+            Self::Regex(regex) => {
+                let captures = regex
+                    .captures(&response.body)
+                    .ok_or_else(|| String::from("no match"))?;
+                let found = captures.get(1).or_else(|| captures.get(0));
+                Ok(found.map_or(String::new(), |m| m.as_str().to_string()))
+            }
+        }
+    }
+}
+
+/// Validator for the extractions table
+pub fn validate_extraction(row: &KeyValue) -> Option<String> {
+    if row.key.contains("{{") || row.key.contains("}}") {
+        return Some(String::from("variable name without braces"));
+    }
+    Extraction::parse(&row.value).err()
+}
+
+/// Applies the enabled extraction rules to a response.
+/// Returns the name of each variable with its value or why it could not be extracted.
+pub fn extract(
+    rules: &[KeyValue],
+    response: &ReceivedResponse,
+) -> Vec<(String, Result<String, String>)> {
+    rules
+        .iter()
+        .filter(|row| row.enabled && !row.key.trim().is_empty())
+        .map(|row| {
+            let value = Extraction::parse(&row.value).and_then(|rule| rule.extract(response));
+            (row.key.trim().to_string(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::{
+        header::{HeaderMap, HeaderValue, SET_COOKIE},
+        StatusCode, Url,
+    };
+
+    use super::extract;
+    use crate::{collection::KeyValue, http::ReceivedResponse};
+
+    #[test]
+    fn extract_rules() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("abc"));
+        headers.insert(
+            SET_COOKIE,
+            HeaderValue::from_static("session=s3cr3t; Path=/"),
+        );
+        let response = ReceivedResponse {
+            status: StatusCode::OK,
+            url: Url::parse("http://localhost/login").unwrap(),
+            headers,
+            body: String::from(r#"{"token": "t0k", "user": {"id": 7}}"#),
+            time: Duration::ZERO,
+            reused: false,
+            cookies: vec![],
+            redirects: vec![],
+            retries: 0,
+            timing: Default::default(),
+        };
+        let rules = [
+            ("token", "json $.token"),
+            ("id", "json /user/id"),
+            ("request", "header X-Request-Id"),
+            ("session", "cookie session"),
+            ("digits", r#"regex "id": (\d+)"#),
+            ("missing", "json $.nope"),
+            ("invalid", "xpath //a"),
+        ]
+        .map(|(key, value)| KeyValue::new(key, value));
+        let values: Vec<_> = extract(&rules, &response)
+            .into_iter()
+            .map(|(_, value)| value.map_err(|_| ()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Ok(String::from("t0k")),
+                Ok(String::from("7")),
+                Ok(String::from("abc")),
+                Ok(String::from("s3cr3t")),
+                Ok(String::from("7")),
+                Err(()),
+                Err(()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,563 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+/// A jq-like filter, it turns one input into any number of outputs
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    /// `.`
+    Identity,
+    /// `.key` or `.["key"]`
+    Field(String),
+    /// `.[0]`, negative indexes count from the end
+    Index(i64),
+    /// `.[1:3]`
+    Slice(Option<i64>, Option<i64>),
+    /// `.[]`
+    Iterate,
+    /// `a | b`
+    Pipe(Box<Filter>, Box<Filter>),
+    /// `[a]`
+    Collect(Box<Filter>),
+    /// `map(a)`
+    Map(Box<Filter>),
+    /// `select(a)`
+    Select(Box<Filter>),
+    Keys,
+    Length,
+    Literal(Value),
+    Compare(Box<Filter>, Operator, Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(Operator),
+    Pipe,
+    Colon,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+}
+
+/// Applies a filter to a JSON value. Filters with several outputs, like `.[]`, return them as an array.
+pub fn apply(filter: &str, value: &Value) -> Result<Value, String> {
+    let filter = parse(filter)?;
+    let mut outputs = eval(&filter, value)?;
+    Ok(if outputs.len() == 1 {
+        outputs.remove(0)
+    } else {
+        Value::Array(outputs)
+    })
+}
+
+fn parse(filter: &str) -> Result<Filter, String> {
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        pos: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Ok(Filter::Identity);
+    }
+    let filter = parser.pipe()?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(token) => Err(format!("Unexpected {}", describe(token))),
+    }
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = filter.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '.' => Token::Dot,
+            '|' => Token::Pipe,
+            ':' => Token::Colon,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '=' | '!' | '<' | '>' => {
+                let eq = chars.next_if_eq(&'=').is_some();
+                Token::Op(match (c, eq) {
+                    ('=', true) => Operator::Eq,
+                    ('!', true) => Operator::Ne,
+                    ('<', false) => Operator::Lt,
+                    ('<', true) => Operator::Le,
+                    ('>', false) => Operator::Gt,
+                    ('>', true) => Operator::Ge,
+                    _ => return Err(format!("Unexpected '{c}'")),
+                })
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(c) => text.push(c),
+                            None => return Err(String::from("Unterminated string")),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err(String::from("Unterminated string")),
+                    }
+                }
+                Token::Str(text)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut number = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                Token::Num(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number {number}"))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                match ident.as_str() {
+                    "and" => Token::Op(Operator::And),
+                    "or" => Token::Op(Operator::Or),
+                    _ => Token::Ident(ident),
+                }
+            }
+            c => return Err(format!("Unexpected '{c}'")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Dot => String::from("'.'"),
+        Token::Ident(ident) => format!("'{ident}'"),
+        Token::Str(text) => format!("\"{text}\""),
+        Token::Num(number) => number.to_string(),
+        Token::Op(_) => String::from("operator"),
+        Token::Pipe => String::from("'|'"),
+        Token::Colon => String::from("':'"),
+        Token::LBracket => String::from("'['"),
+        Token::RBracket => String::from("']'"),
+        Token::LParen => String::from("'('"),
+        Token::RParen => String::from("')'"),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!(
+                "Expected {} but found {}",
+                describe(&expected),
+                describe(&token)
+            )),
+            None => Err(format!("Expected {}", describe(&expected))),
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Filter, String> {
+        let mut filter = self.binary(0)?;
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            filter = Filter::Pipe(Box::new(filter), Box::new(self.binary(0)?));
+        }
+        Ok(filter)
+    }
+
+    /// Parses `or`, then `and`, then comparisons, which bind tighter
+    fn binary(&mut self, level: usize) -> Result<Filter, String> {
+        const LEVELS: [&[Operator]; 3] = [
+            &[Operator::Or],
+            &[Operator::And],
+            &[
+                Operator::Eq,
+                Operator::Ne,
+                Operator::Lt,
+                Operator::Le,
+                Operator::Gt,
+                Operator::Ge,
+            ],
+        ];
+        if level == LEVELS.len() {
+            return self.postfix();
+        }
+        let mut filter = self.binary(level + 1)?;
+        while let Some(&Token::Op(op)) = self.peek() {
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            filter = Filter::Compare(Box::new(filter), op, Box::new(right));
+        }
+        Ok(filter)
+    }
+
+    fn postfix(&mut self) -> Result<Filter, String> {
+        let mut filter = self.term()?;
+        loop {
+            let suffix = match (self.peek(), self.tokens.get(self.pos + 1)) {
+                (Some(Token::Dot), Some(Token::Ident(_) | Token::Str(_))) => {
+                    self.pos += 1;
+                    self.field()?
+                }
+                (Some(Token::Dot), Some(Token::LBracket)) => {
+                    self.pos += 1;
+                    self.brackets()?
+                }
+                (Some(Token::LBracket), _) => self.brackets()?,
+                _ => return Ok(filter),
+            };
+            filter = Filter::Pipe(Box::new(filter), Box::new(suffix));
+        }
+    }
+
+    fn term(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            Some(Token::Dot) => match self.peek() {
+                Some(Token::Ident(_) | Token::Str(_)) => self.field(),
+                _ => Ok(Filter::Identity),
+            },
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "keys" => Ok(Filter::Keys),
+                "length" => Ok(Filter::Length),
+                "true" => Ok(Filter::Literal(Value::Bool(true))),
+                "false" => Ok(Filter::Literal(Value::Bool(false))),
+                "null" => Ok(Filter::Literal(Value::Null)),
+                "map" | "select" => {
+                    self.expect(Token::LParen)?;
+                    let inner = Box::new(self.pipe()?);
+                    self.expect(Token::RParen)?;
+                    Ok(if ident == "map" {
+                        Filter::Map(inner)
+                    } else {
+                        Filter::Select(inner)
+                    })
+                }
+                _ => Err(format!("Unknown function '{ident}'")),
+            },
+            Some(Token::Str(text)) => Ok(Filter::Literal(Value::String(text))),
+            Some(Token::Num(number)) => Ok(Filter::Literal(number_value(number))),
+            Some(Token::LBracket) => {
+                if self.peek() == Some(&Token::RBracket) {
+                    self.pos += 1;
+                    return Ok(Filter::Literal(Value::Array(vec![])));
+                }
+                let inner = self.pipe()?;
+                self.expect(Token::RBracket)?;
+                Ok(Filter::Collect(Box::new(inner)))
+            }
+            Some(Token::LParen) => {
+                let inner = self.pipe()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(token) => Err(format!("Unexpected {}", describe(&token))),
+            None => Err(String::from("Unexpected end of filter")),
+        }
+    }
+
+    /// Parses the name after a `.`
+    fn field(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            Some(Token::Ident(name) | Token::Str(name)) => Ok(Filter::Field(name)),
+            _ => Err(String::from("Expected a key after '.'")),
+        }
+    }
+
+    /// Parses `[]`, `["key"]`, `[index]` or `[start:end]`
+    fn brackets(&mut self) -> Result<Filter, String> {
+        self.expect(Token::LBracket)?;
+        let filter = match self.next() {
+            Some(Token::RBracket) => return Ok(Filter::Iterate),
+            Some(Token::Str(name)) => Filter::Field(name),
+            Some(Token::Colon) => Filter::Slice(None, Some(self.integer()?)),
+            Some(Token::Num(number)) => {
+                let start = integer(number)?;
+                if self.peek() == Some(&Token::Colon) {
+                    self.pos += 1;
+                    let end = match self.peek() {
+                        Some(Token::RBracket) => None,
+                        _ => Some(self.integer()?),
+                    };
+                    Filter::Slice(Some(start), end)
+                } else {
+                    Filter::Index(start)
+                }
+            }
+            _ => return Err(String::from("Expected an index, a slice or a key in '[]'")),
+        };
+        self.expect(Token::RBracket)?;
+        Ok(filter)
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        match self.next() {
+            Some(Token::Num(number)) => integer(number),
+            _ => Err(String::from("Expected an index")),
+        }
+    }
+}
+
+fn integer(number: f64) -> Result<i64, String> {
+    if number.fract() == 0.0 {
+        Ok(number as i64)
+    } else {
+        Err(format!("Index {number} is not an integer"))
+    }
+}
+
+fn number_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Value::from(number as i64)
+    } else {
+        Value::from(number)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Resolves a possibly negative slice bound into a position in a sequence of `len` items
+fn position(index: i64, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(index.unsigned_abs() as usize)
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+/// Orders values like jq: null, false, true, numbers, strings, arrays, objects
+fn compare(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    };
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(_), Value::Object(_)) => a.to_string().cmp(&b.to_string()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn eval(filter: &Filter, input: &Value) -> Result<Vec<Value>, String> {
+    Ok(match filter {
+        Filter::Identity => vec![input.clone()],
+        Filter::Field(name) => match input {
+            Value::Object(map) => vec![map.get(name).cloned().unwrap_or(Value::Null)],
+            Value::Null => vec![Value::Null],
+            _ => return Err(format!("Cannot index {} with \"{name}\"", type_name(input))),
+        },
+        Filter::Index(index) => match input {
+            Value::Array(items) => {
+                let i = if *index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    *index
+                };
+                let item = usize::try_from(i).ok().and_then(|i| items.get(i));
+                vec![item.cloned().unwrap_or(Value::Null)]
+            }
+            Value::Null => vec![Value::Null],
+            _ => return Err(format!("Cannot index {} with a number", type_name(input))),
+        },
+        Filter::Slice(start, end) => {
+            let range = |len: usize| {
+                let start = start.map_or(0, |start| position(start, len));
+                let end = end.map_or(len, |end| position(end, len));
+                start..end.max(start)
+            };
+            match input {
+                Value::Array(items) => vec![Value::Array(items[range(items.len())].to_vec())],
+                Value::String(text) => {
+                    let chars: Vec<char> = text.chars().collect();
+                    vec![Value::String(chars[range(chars.len())].iter().collect())]
+                }
+                Value::Null => vec![Value::Null],
+                _ => return Err(format!("Cannot slice {}", type_name(input))),
+            }
+        }
+        Filter::Iterate => match input {
+            Value::Array(items) => items.clone(),
+            Value::Object(map) => map.values().cloned().collect(),
+            _ => return Err(format!("Cannot iterate over {}", type_name(input))),
+        },
+        Filter::Pipe(left, right) => {
+            let mut outputs = vec![];
+            for value in eval(left, input)? {
+                outputs.extend(eval(right, &value)?);
+            }
+            outputs
+        }
+        Filter::Collect(inner) => vec![Value::Array(eval(inner, input)?)],
+        Filter::Map(inner) => {
+            let mut outputs = vec![];
+            for value in eval(&Filter::Iterate, input)? {
+                outputs.extend(eval(inner, &value)?);
+            }
+            vec![Value::Array(outputs)]
+        }
+        Filter::Select(condition) => eval(condition, input)?
+            .iter()
+            .filter(|value| truthy(value))
+            .map(|_| input.clone())
+            .collect(),
+        Filter::Keys => match input {
+            Value::Object(map) => vec![Value::Array(
+                map.keys().map(|key| Value::String(key.clone())).collect(),
+            )],
+            Value::Array(items) => vec![Value::Array((0..items.len()).map(Value::from).collect())],
+            _ => return Err(format!("{} has no keys", type_name(input))),
+        },
+        Filter::Length => vec![match input {
+            Value::Null => Value::from(0),
+            Value::Bool(_) => return Err(String::from("boolean has no length")),
+            Value::Number(number) => number_value(number.as_f64().unwrap_or_default().abs()),
+            Value::String(text) => Value::from(text.chars().count()),
+            Value::Array(items) => Value::from(items.len()),
+            Value::Object(map) => Value::from(map.len()),
+        }],
+        Filter::Literal(value) => vec![value.clone()],
+        Filter::Compare(left, op, right) => {
+            let mut outputs = vec![];
+            for b in eval(right, input)? {
+                for a in eval(left, input)? {
+                    let ordering = compare(&a, &b);
+                    outputs.push(Value::Bool(match op {
+                        Operator::Eq => ordering.is_eq(),
+                        Operator::Ne => ordering.is_ne(),
+                        Operator::Lt => ordering.is_lt(),
+                        Operator::Le => ordering.is_le(),
+                        Operator::Gt => ordering.is_gt(),
+                        Operator::Ge => ordering.is_ge(),
+                        Operator::And => truthy(&a) && truthy(&b),
+                        Operator::Or => truthy(&a) || truthy(&b),
+                    }));
+                }
+            }
+            outputs
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::apply;
+
+    #[test]
+    fn apply_filters() {
+        let value = json!({
+            "users": [
+                {"name": "alice", "age": 31, "tags": ["admin"]},
+                {"name": "bob", "age": 25, "tags": []},
+                {"name": "carol", "age": 40, "tags": ["admin", "ops"]}
+            ],
+            "total": 3
+        });
+        let filter = |filter| apply(filter, &value);
+
+        assert_eq!(filter(""), Ok(value.clone()));
+        assert_eq!(filter(".total"), Ok(json!(3)));
+        assert_eq!(filter(".users[-1].name"), Ok(json!("carol")));
+        assert_eq!(filter(".users[5]"), Ok(json!(null)));
+        assert_eq!(
+            filter(".users[1:].age"),
+            Err("Cannot index array with \"age\"".into())
+        );
+        assert_eq!(filter(".users[:2] | map(.age)"), Ok(json!([31, 25])));
+        assert_eq!(
+            filter(".users[].name"),
+            Ok(json!(["alice", "bob", "carol"]))
+        );
+        assert_eq!(
+            filter(".users | map(select(.age > 30 and (.tags | length) > 0)) | map(.name)"),
+            Ok(json!(["alice", "carol"]))
+        );
+        assert_eq!(
+            filter(".users[] | select(.name == \"bob\") | .age"),
+            Ok(json!(25))
+        );
+        assert_eq!(filter("keys"), Ok(json!(["total", "users"])));
+        assert_eq!(
+            filter("[.users[].tags[]]"),
+            Ok(json!(["admin", "admin", "ops"]))
+        );
+        assert_eq!(filter(".[\"total\"]"), Ok(json!(3)));
+
+        assert!(filter(".users[").is_err());
+        assert!(filter(".total[]").is_err());
+        assert!(filter("sort").is_err());
+        assert!(filter(".users )").is_err());
+    }
+}
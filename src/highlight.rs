@@ -0,0 +1,624 @@
+use std::ops::Range;
+
+/// Syntax used to highlight a body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    Plain,
+    Json,
+    /// XML and HTML
+    Markup,
+    JavaScript,
+    Yaml,
+    /// `application/x-www-form-urlencoded`
+    Form,
+}
+
+impl Language {
+    pub fn from_content_type(content_type: &str) -> Self {
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match media_type.as_str() {
+            t if t.contains("json") => Self::Json,
+            t if t.ends_with("xml") || t.contains("html") => Self::Markup,
+            t if t.contains("javascript") || t.contains("ecmascript") => Self::JavaScript,
+            t if t.contains("yaml") => Self::Yaml,
+            "application/x-www-form-urlencoded" => Self::Form,
+            _ => Self::Plain,
+        }
+    }
+
+    /// Language of a body by its content type, or by its first character when the
+    /// content type is missing or unknown
+    pub fn detect(content_type: Option<&str>, text: &str) -> Self {
+        match content_type.map(Self::from_content_type) {
+            Some(Self::Plain) | None => match text.trim_start().chars().next() {
+                Some('{' | '[') => Self::Json,
+                Some('<') => Self::Markup,
+                _ => Self::Plain,
+            },
+            Some(language) => language,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Key,
+    String,
+    Number,
+    Keyword,
+    Punctuation,
+    Comment,
+    Tag,
+    Attribute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// Byte range in the line
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// State carried from the end of a line to the next, for tokens spanning lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineState {
+    #[default]
+    Normal,
+    /// Inside a `/* */` or `<!-- -->` comment
+    Comment,
+    /// Inside a markup tag, after its name
+    Tag,
+    /// Inside a JavaScript template string
+    Template,
+}
+
+/// Highlights the lines of a text on demand and keeps the result, so only lines that
+/// changed since the last call are highlighted again
+#[derive(Debug, Default)]
+pub struct Highlighter {
+    language: Language,
+    /// Tokens of each highlighted line and the state at its end
+    lines: Vec<(Vec<Token>, LineState)>,
+}
+
+impl Highlighter {
+    pub fn set_language(&mut self, language: Language) {
+        if language != self.language {
+            self.language = language;
+            self.lines.clear();
+        }
+    }
+
+    /// Drops the highlighting of `row` and the lines after it, after the text changed there
+    pub fn invalidate(&mut self, row: usize) {
+        self.lines.truncate(row);
+    }
+
+    /// Tokens of the line at `row`, the lines before it are highlighted when needed
+    pub fn line<S: AsRef<str>>(&mut self, lines: &[S], row: usize) -> &[Token] {
+        if lines.is_empty() {
+            return &[];
+        }
+        while self.lines.len() <= row.min(lines.len().saturating_sub(1)) {
+            let state = self.lines.last().map_or(LineState::Normal, |line| line.1);
+            let text = lines[self.lines.len()].as_ref();
+            self.lines.push(highlight_line(self.language, text, state));
+        }
+        self.lines.get(row).map_or(&[], |line| &line.0)
+    }
+}
+
+/// Highlights every line of `text`
+pub fn highlight(language: Language, text: &str) -> Vec<Vec<Token>> {
+    let mut state = LineState::Normal;
+    text.lines()
+        .map(|line| {
+            let (tokens, next) = highlight_line(language, line, state);
+            state = next;
+            tokens
+        })
+        .collect()
+}
+
+pub fn highlight_line(language: Language, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+    let mut lexer = Lexer {
+        line,
+        bytes: line.as_bytes(),
+        i: 0,
+        tokens: vec![],
+    };
+    let state = match language {
+        Language::Plain => state,
+        Language::Json => {
+            lexer.json();
+            LineState::Normal
+        }
+        Language::Markup => lexer.markup(state),
+        Language::JavaScript => lexer.javascript(state),
+        Language::Yaml => {
+            lexer.yaml();
+            LineState::Normal
+        }
+        Language::Form => {
+            lexer.form();
+            LineState::Normal
+        }
+    };
+    (lexer.tokens, state)
+}
+
+const JS_KEYWORDS: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "from",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "null",
+    "of",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "yield",
+];
+
+/// Tokens only start and end at ASCII characters, so every range is on a char boundary
+struct Lexer<'a> {
+    line: &'a str,
+    bytes: &'a [u8],
+    i: usize,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn push(&mut self, end: usize, kind: TokenKind) {
+        if end > self.i {
+            self.tokens.push(Token {
+                range: self.i..end,
+                kind,
+            });
+        }
+        self.i = end;
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.i).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.line[self.i..]
+    }
+
+    /// End of the string starting at the current quote, `None` when it is not closed
+    fn string_end(&self, quote: u8) -> Option<usize> {
+        let mut j = self.i + 1;
+        while j < self.bytes.len() {
+            match self.bytes[j] {
+                b'\\' => j += 2,
+                c if c == quote => return Some(j + 1),
+                _ => j += 1,
+            }
+        }
+        None
+    }
+
+    fn word_end(&self, part: impl Fn(u8) -> bool) -> usize {
+        let mut j = self.i;
+        while j < self.bytes.len() && part(self.bytes[j]) {
+            j += 1;
+        }
+        j
+    }
+
+    fn number_end(&self) -> usize {
+        let mut j = self.i + 1;
+        while j < self.bytes.len() {
+            match self.bytes[j] {
+                b'0'..=b'9' | b'.' | b'_' | b'a'..=b'z' | b'A'..=b'Z' => j += 1,
+                b'+' | b'-' if matches!(self.bytes[j - 1], b'e' | b'E') => j += 1,
+                _ => break,
+            }
+        }
+        j
+    }
+
+    /// Whether the next character after `j`, ignoring spaces, is a colon
+    fn colon_after(&self, j: usize) -> bool {
+        self.line[j..].trim_start().starts_with(':')
+    }
+
+    fn json(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b'"' => {
+                    let end = self.string_end(b'"').unwrap_or(self.bytes.len());
+                    let kind = if self.colon_after(end) {
+                        TokenKind::Key
+                    } else {
+                        TokenKind::String
+                    };
+                    self.push(end, kind);
+                }
+                b'-' | b'0'..=b'9' => self.push(self.number_end(), TokenKind::Number),
+                b'{' | b'}' | b'[' | b']' | b',' | b':' => {
+                    self.push(self.i + 1, TokenKind::Punctuation)
+                }
+                c if c.is_ascii_alphabetic() => {
+                    let end = self.word_end(|c| c.is_ascii_alphanumeric());
+                    match &self.line[self.i..end] {
+                        "true" | "false" | "null" => self.push(end, TokenKind::Keyword),
+                        _ => self.i = end,
+                    }
+                }
+                _ => self.i += 1,
+            }
+        }
+    }
+
+    fn javascript(&mut self, mut state: LineState) -> LineState {
+        match state {
+            LineState::Comment => match self.rest().find("*/") {
+                Some(end) => self.push(end + 2, TokenKind::Comment),
+                None => {
+                    self.push(self.bytes.len(), TokenKind::Comment);
+                    return state;
+                }
+            },
+            LineState::Template => match self.template_end() {
+                Some(end) => self.push(end, TokenKind::String),
+                None => {
+                    self.push(self.bytes.len(), TokenKind::String);
+                    return state;
+                }
+            },
+            _ => (),
+        }
+        state = LineState::Normal;
+        while let Some(c) = self.peek() {
+            match c {
+                b'/' if self.rest().starts_with("//") => {
+                    self.push(self.bytes.len(), TokenKind::Comment)
+                }
+                b'/' if self.rest().starts_with("/*") => match self.rest()[2..].find("*/") {
+                    Some(end) => self.push(self.i + 2 + end + 2, TokenKind::Comment),
+                    None => {
+                        self.push(self.bytes.len(), TokenKind::Comment);
+                        state = LineState::Comment;
+                    }
+                },
+                b'"' | b'\'' => {
+                    let end = self.string_end(c).unwrap_or(self.bytes.len());
+                    self.push(end, TokenKind::String);
+                }
+                b'`' => match self.string_end(b'`') {
+                    Some(end) => self.push(end, TokenKind::String),
+                    None => {
+                        self.push(self.bytes.len(), TokenKind::String);
+                        state = LineState::Template;
+                    }
+                },
+                b'0'..=b'9' => self.push(self.number_end(), TokenKind::Number),
+                b'{' | b'}' | b'[' | b']' | b'(' | b')' | b',' | b';' | b':' => {
+                    self.push(self.i + 1, TokenKind::Punctuation)
+                }
+                c if c.is_ascii_alphabetic() || c == b'_' || c == b'$' => {
+                    let end =
+                        self.word_end(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'$');
+                    if JS_KEYWORDS.contains(&&self.line[self.i..end]) {
+                        self.push(end, TokenKind::Keyword);
+                    } else if self.colon_after(end) {
+                        self.push(end, TokenKind::Key);
+                    } else {
+                        self.i = end;
+                    }
+                }
+                _ => self.i += 1,
+            }
+        }
+        state
+    }
+
+    /// End of a template string that started on a previous line
+    fn template_end(&self) -> Option<usize> {
+        let mut j = 0;
+        while j < self.bytes.len() {
+            match self.bytes[j] {
+                b'\\' => j += 2,
+                b'`' => return Some(j + 1),
+                _ => j += 1,
+            }
+        }
+        None
+    }
+
+    fn markup(&mut self, mut state: LineState) -> LineState {
+        while self.i < self.bytes.len() {
+            state = match state {
+                LineState::Comment => match self.rest().find("-->") {
+                    Some(end) => {
+                        self.push(self.i + end + 3, TokenKind::Comment);
+                        LineState::Normal
+                    }
+                    None => {
+                        self.push(self.bytes.len(), TokenKind::Comment);
+                        LineState::Comment
+                    }
+                },
+                LineState::Tag => self.markup_tag(),
+                _ => self.markup_text(),
+            };
+        }
+        state
+    }
+
+    fn markup_text(&mut self) -> LineState {
+        match self.peek() {
+            Some(b'<') if self.rest().starts_with("<!--") => LineState::Comment,
+            Some(b'<') => {
+                let start = if matches!(self.bytes.get(self.i + 1), Some(b'/' | b'?' | b'!')) {
+                    2
+                } else {
+                    1
+                };
+                self.push(self.i + start, TokenKind::Punctuation);
+                let end = self.word_end(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, b':' | b'_' | b'-' | b'.')
+                });
+                self.push(end, TokenKind::Tag);
+                LineState::Tag
+            }
+            Some(b'&') => {
+                match self.rest().find(';').filter(|&end| {
+                    self.rest()[1..end]
+                        .bytes()
+                        .all(|c| c.is_ascii_alphanumeric() || c == b'#')
+                }) {
+                    Some(end) => self.push(self.i + end + 1, TokenKind::Keyword),
+                    None => self.i += 1,
+                }
+                LineState::Normal
+            }
+            _ => {
+                self.i = self
+                    .rest()
+                    .find(['<', '&'])
+                    .map_or(self.bytes.len(), |j| self.i + j);
+                LineState::Normal
+            }
+        }
+    }
+
+    fn markup_tag(&mut self) -> LineState {
+        match self.peek() {
+            Some(b'>') => {
+                self.push(self.i + 1, TokenKind::Punctuation);
+                LineState::Normal
+            }
+            Some(b'/' | b'?') if matches!(self.bytes.get(self.i + 1), Some(b'>')) => {
+                self.push(self.i + 2, TokenKind::Punctuation);
+                LineState::Normal
+            }
+            Some(c @ (b'"' | b'\'')) => {
+                let end = self.string_end(c).unwrap_or(self.bytes.len());
+                self.push(end, TokenKind::String);
+                LineState::Tag
+            }
+            Some(b'=') => {
+                self.push(self.i + 1, TokenKind::Punctuation);
+                LineState::Tag
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let end = self.word_end(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, b':' | b'_' | b'-' | b'.')
+                });
+                self.push(end, TokenKind::Attribute);
+                LineState::Tag
+            }
+            _ => {
+                self.i += 1;
+                LineState::Tag
+            }
+        }
+    }
+
+    fn yaml(&mut self) {
+        self.i = self.line.len() - self.line.trim_start().len();
+        let rest = self.rest();
+        if rest.starts_with('#') {
+            return self.push(self.bytes.len(), TokenKind::Comment);
+        }
+        if rest.starts_with("---") || rest.starts_with("...") {
+            return self.push(self.i + 3, TokenKind::Punctuation);
+        }
+        while self.rest().starts_with("- ") || self.rest() == "-" {
+            self.push(self.i + 1, TokenKind::Punctuation);
+            self.i = self.bytes.len() - self.rest().trim_start().len();
+        }
+        // A comment starts at a # after a space
+        let end = self
+            .rest()
+            .find(" #")
+            .map_or(self.bytes.len(), |j| self.i + j);
+        if let Some(colon) = self.line[self.i..end]
+            .find(": ")
+            .or_else(|| {
+                self.line[self.i..end]
+                    .trim_end()
+                    .strip_suffix(':')
+                    .map(str::len)
+            })
+            .filter(|_| !matches!(self.peek(), Some(b'"' | b'\'' | b'{' | b'[')))
+        {
+            self.push(self.i + colon, TokenKind::Key);
+            self.push(self.i + 1, TokenKind::Punctuation);
+            self.i = end - self.line[self.i..end].trim_start().len();
+        }
+        let value = self.line[self.i..end].trim_end();
+        let kind = match value {
+            "" => None,
+            "true" | "false" | "null" | "~" | "yes" | "no" => Some(TokenKind::Keyword),
+            "|" | ">" | "|-" | ">-" => Some(TokenKind::Punctuation),
+            v if v.starts_with(['&', '*']) => Some(TokenKind::Attribute),
+            v if v.parse::<f64>().is_ok() => Some(TokenKind::Number),
+            _ => Some(TokenKind::String),
+        };
+        if let Some(kind) = kind {
+            self.push(self.i + value.len(), kind);
+        }
+        if end < self.bytes.len() {
+            self.i = end + 1;
+            self.push(self.bytes.len(), TokenKind::Comment);
+        }
+    }
+
+    fn form(&mut self) {
+        while self.i < self.bytes.len() {
+            let end = self
+                .rest()
+                .find('&')
+                .map_or(self.bytes.len(), |j| self.i + j);
+            match self.line[self.i..end].find('=') {
+                Some(equals) => {
+                    self.push(self.i + equals, TokenKind::Key);
+                    self.push(self.i + 1, TokenKind::Punctuation);
+                    self.push(end, TokenKind::String);
+                }
+                None => self.push(end, TokenKind::Key),
+            }
+            self.push((end + 1).min(self.bytes.len()), TokenKind::Punctuation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{highlight, highlight_line, Highlighter, Language, LineState, TokenKind};
+
+    fn kinds(language: Language, line: &str) -> Vec<(&str, TokenKind)> {
+        highlight_line(language, line, LineState::Normal)
+            .0
+            .into_iter()
+            .map(|token| (&line[token.range], token.kind))
+            .collect()
+    }
+
+    #[test]
+    fn highlight_languages() {
+        use TokenKind::*;
+
+        assert_eq!(
+            Language::detect(Some("application/problem+json; charset=utf-8"), ""),
+            Language::Json
+        );
+        assert_eq!(
+            Language::detect(Some("text/plain"), " <a/>"),
+            Language::Markup
+        );
+        assert_eq!(
+            kinds(Language::Json, r#"{"id": -1.5e3, "ok": [true, "ä\""]}"#),
+            [
+                ("{", Punctuation),
+                (r#""id""#, Key),
+                (":", Punctuation),
+                ("-1.5e3", Number),
+                (",", Punctuation),
+                (r#""ok""#, Key),
+                (":", Punctuation),
+                ("[", Punctuation),
+                ("true", Keyword),
+                (",", Punctuation),
+                (r#""ä\"""#, String),
+                ("]", Punctuation),
+                ("}", Punctuation),
+            ]
+        );
+        assert_eq!(
+            kinds(Language::Markup, r#"<a href="/x">&amp;</a>"#),
+            [
+                ("<", Punctuation),
+                ("a", Tag),
+                ("href", Attribute),
+                ("=", Punctuation),
+                (r#""/x""#, String),
+                (">", Punctuation),
+                ("&amp;", Keyword),
+                ("</", Punctuation),
+                ("a", Tag),
+                (">", Punctuation),
+            ]
+        );
+        assert_eq!(
+            kinds(Language::Yaml, "  - name: 'x' # note"),
+            [
+                ("-", Punctuation),
+                ("name", Key),
+                (":", Punctuation),
+                ("'x'", String),
+                ("# note", Comment),
+            ]
+        );
+        assert_eq!(
+            kinds(Language::Form, "a=1&b"),
+            [
+                ("a", Key),
+                ("=", Punctuation),
+                ("1", String),
+                ("&", Punctuation),
+                ("b", Key),
+            ]
+        );
+
+        let lines = highlight(Language::JavaScript, "let a = 1; /* one\ntwo */ b");
+        assert_eq!(lines[1][0].range, 0..6);
+        assert_eq!(lines[1][0].kind, Comment);
+    }
+
+    #[test]
+    fn highlighter_is_incremental() {
+        let mut lines = vec!["<!-- a", "b -->", "<p>"];
+        let mut highlighter = Highlighter::default();
+        highlighter.set_language(Language::Markup);
+        assert_eq!(highlighter.line(&lines, 1)[0].kind, TokenKind::Comment);
+        assert_eq!(highlighter.lines.len(), 2);
+
+        lines[0] = "<p>";
+        highlighter.invalidate(0);
+        assert_eq!(highlighter.line(&lines, 2)[1].kind, TokenKind::Tag);
+        assert_eq!(highlighter.line(&lines, 1).len(), 0);
+    }
+}
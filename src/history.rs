@@ -0,0 +1,174 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collection::{KeyValue, SavedRequest},
+    config,
+    http::ReceivedResponse,
+    timing::Timing,
+};
+
+/// Number of entries that are kept, older entries are dropped
+const MAX_ENTRIES: usize = 200;
+
+/// A response as it is stored in the history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryResponse {
+    pub status: u16,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<KeyValue>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl HistoryResponse {
+    pub fn new(response: &ReceivedResponse) -> Self {
+        Self {
+            status: response.status.as_u16(),
+            url: response.url.to_string(),
+            headers: response
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    KeyValue::new(name.as_str(), String::from_utf8_lossy(value.as_bytes()))
+                })
+                .collect(),
+            body: response.body.clone(),
+        }
+    }
+
+    pub fn header_map(&self) -> HeaderMap {
+        self.headers
+            .iter()
+            .filter_map(|h| {
+                Some((
+                    HeaderName::from_bytes(h.key.as_bytes()).ok()?,
+                    HeaderValue::from_str(&h.value).ok()?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// A request that was sent, with variables substituted, and its outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch at which the request was sent
+    pub timestamp: u64,
+    pub request: SavedRequest,
+    /// `Err` with a message when no response was received
+    pub response: Result<HistoryResponse, String>,
+    pub time: Duration,
+    /// Phases of the request, missing when no response was received and in older entries
+    #[serde(default)]
+    pub timing: Option<Timing>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        request: SavedRequest,
+        sent: SystemTime,
+        response: Result<&ReceivedResponse, &String>,
+    ) -> Self {
+        Self {
+            timestamp: sent.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            request,
+            time: response.map_or(Duration::ZERO, |r| r.time),
+            timing: response.ok().map(|r| r.timing.clone()),
+            response: response.map(HistoryResponse::new).map_err(String::clone),
+        }
+    }
+
+    /// Size of the response body in bytes
+    pub fn size(&self) -> usize {
+        self.response.as_ref().map_or(0, |r| r.body.len())
+    }
+
+    /// Time of day at which the request was sent (UTC)
+    pub fn time_of_day(&self) -> String {
+        let secs = self.timestamp % (24 * 60 * 60);
+        format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+/// Every request that was sent, newest first, persisted as a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        config::data_dir().join("history.json")
+    }
+
+    /// Loads the history at `path`, an empty history is returned when the file does not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut history = match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str::<History>(&data).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => History::default(),
+            Err(e) => return Err(e),
+        };
+        history.path = path;
+        Ok(history)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string(self)?;
+        fs::write(&self.path, data)
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{History, HistoryEntry};
+    use crate::collection::SavedRequest;
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let mut history = History::load(&path).unwrap();
+        assert!(history.entries.is_empty());
+
+        let sent = UNIX_EPOCH + Duration::from_secs(3600 + 2 * 60 + 3);
+        let error = String::from("Request failed");
+        history.push(HistoryEntry::new(
+            SavedRequest::default(),
+            sent,
+            Err(&error),
+        ));
+        history.save().unwrap();
+
+        let loaded = History::load(&path).unwrap();
+        assert_eq!(loaded.entries, history.entries);
+        assert_eq!(loaded.entries[0].time_of_day(), "01:02:03");
+        assert_eq!(loaded.entries[0].response, Err(error));
+    }
+}
@@ -1,62 +1,836 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper::{body::HttpBody, Body, Request, Response};
+use percent_encoding::percent_decode_str;
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Body, IntoUrl, Method,
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, COOKIE, LOCATION,
+        PROXY_AUTHORIZATION,
+    },
+    Method, StatusCode, Url,
 };
 use tokio::time::Instant;
 
-pub async fn http_request<T, U>(
-    method: reqwest::Method,
-    uri: U,
-    headers: HeaderMap,
-    content_type: HeaderValue,
-    body: T,
-) -> Result<(reqwest::Response, Duration), Box<dyn std::error::Error>>
-where
-    U: IntoUrl,
-    T: Into<Body> + Default,
-{
-    let client = reqwest::Client::new();
-    let request = match method {
-        Method::GET => client.get(uri),
-        Method::POST => client.post(uri),
-        Method::PUT => client.put(uri),
-        Method::DELETE => client.delete(uri),
-        Method::PATCH => client.patch(uri),
-        Method::HEAD => client.head(uri),
-        Method::OPTIONS => client.request(Method::OPTIONS, uri),
-        _ => todo!(),
+use crate::{
+    body,
+    client::{HttpClient, Pool, RequestSettings},
+    collection::{ApiKeyLocation, Auth, BodyMode, KeyValue, SavedRequest},
+    connector::ConnectionInfo,
+    cookies::{self, Cookie, CookieJar},
+    environment::strip_placeholders,
+    query,
+    timing::Timing,
+};
+
+/// Checks that a row forms a valid HTTP header, variables are assumed to be valid
+pub fn validate_header(row: &KeyValue) -> Option<String> {
+    if let Err(e) = HeaderName::from_bytes(strip_placeholders(&row.key).as_bytes()) {
+        return Some(format!("invalid header name ({e})"));
+    }
+    if let Err(e) = HeaderValue::from_str(&strip_placeholders(&row.value)) {
+        return Some(format!("invalid header value ({e})"));
+    }
+    None
+}
+
+/// Builds a header map from the given rows, failing on the first invalid header
+pub fn header_map<'a>(rows: impl IntoIterator<Item = &'a KeyValue>) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for row in rows {
+        let name = HeaderName::from_bytes(row.key.as_bytes())
+            .map_err(|e| format!("Invalid header name '{}': {e}", row.key))?;
+        let value = HeaderValue::from_str(&row.value)
+            .map_err(|e| format!("Invalid value for header '{}': {e}", row.key))?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// Adds the credentials of `auth` to the headers, or to the query of `url`.
+/// Returns the url the request should be sent to.
+pub fn apply_auth(auth: &Auth, url: &str, headers: &mut HeaderMap) -> Result<String, String> {
+    let authorization = match auth {
+        Auth::None => None,
+        Auth::Basic { username, password } => Some(format!(
+            "Basic {}",
+            STANDARD.encode(format!("{username}:{password}"))
+        )),
+        Auth::Bearer { token } => Some(format!("Bearer {token}")),
+        Auth::ApiKey {
+            key,
+            value,
+            location: ApiKeyLocation::Header,
+        } => {
+            let row = KeyValue::new(key.as_str(), value.as_str());
+            headers.extend(header_map([&row])?);
+            None
+        }
+        Auth::ApiKey {
+            key,
+            value,
+            location: ApiKeyLocation::Query,
+        } => {
+            let mut params = query::parse(url);
+            params.push(KeyValue::new(key.as_str(), value.as_str()));
+            return Ok(query::with_query(url, &params));
+        }
     };
-    let request = request
-        .body(body)
-        .headers(headers)
-        .header("content-type", content_type);
+    if let Some(authorization) = authorization {
+        let mut value = HeaderValue::from_str(&authorization)
+            .map_err(|e| format!("Invalid credentials: {e}"))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    Ok(url.to_string())
+}
+
+/// A request with its headers and authentication resolved, ready to be sent. The cookies of
+/// the jar are added to each hop when it is sent.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub content_type: HeaderValue,
+    pub body: Vec<u8>,
+    /// Rows of the settings table, see [`RequestSettings`]
+    pub settings: Vec<KeyValue>,
+    /// Copy of the cookie jar when it is enabled, the cookies of each hop are sent from it
+    pub jar: Option<CookieJar>,
+    /// Header of an API key, which is not sent to other origins
+    pub api_key_header: Option<HeaderName>,
+}
+
+impl PreparedRequest {
+    pub fn new(request: &SavedRequest, jar: Option<&CookieJar>) -> Result<Self, String> {
+        let mut headers = header_map(
+            request
+                .headers
+                .iter()
+                .filter(|h| h.enabled && !h.key.is_empty()),
+        )?;
+        let body = body::encode(request)?;
+        // A content type set in the headers tab takes precedence over the one of the body mode,
+        // except for multipart bodies which need their boundary
+        let content_type = match headers.remove(CONTENT_TYPE) {
+            Some(content_type) if request.body_mode != BodyMode::Multipart => content_type,
+            _ => body.content_type,
+        };
+
+        let url = apply_auth(&request.auth, &request.url, &mut headers)?;
+        let api_key_header = match &request.auth {
+            Auth::ApiKey {
+                key,
+                location: ApiKeyLocation::Header,
+                ..
+            } => HeaderName::from_bytes(key.as_bytes()).ok(),
+            _ => None,
+        };
+
+        Ok(Self {
+            method: request.method(),
+            url,
+            headers,
+            content_type,
+            body: body.bytes,
+            settings: request.settings.clone(),
+            jar: jar.filter(|jar| jar.enabled).cloned(),
+            api_key_header,
+        })
+    }
+
+    /// Sends the request, following redirects and retrying failed attempts as the settings of
+    /// `client` and of the request say
+    pub async fn send(self, client: &HttpClient) -> Result<ReceivedResponse, String> {
+        let settings = client.settings().for_request(&self.settings)?;
+        let pool = client.pool(settings.connect_timeout)?;
+        let mut retries = 0;
+        loop {
+            let result = match settings.timeout {
+                // The timeout is for the whole request, including the redirects
+                Some(timeout) => tokio::time::timeout(timeout, self.send_once(&pool, &settings))
+                    .await
+                    .unwrap_or_else(|_| Err(SendError::timed_out(timeout))),
+                None => self.send_once(&pool, &settings).await,
+            };
+            let retry = match &result {
+                Ok(response) => settings.retries_status(&self.method, response.status),
+                Err(SendError::Connect(_)) => true,
+                Err(SendError::Other(_)) => false,
+            };
+            if !retry || retries == settings.retries {
+                return match result {
+                    Ok(response) => Ok(ReceivedResponse {
+                        retries,
+                        ..response
+                    }),
+                    Err(SendError::Connect(e) | SendError::Other(e)) if retries > 0 => {
+                        Err(format!("{e} (after {retries} retries)"))
+                    }
+                    Err(SendError::Connect(e) | SendError::Other(e)) => Err(e),
+                };
+            }
+            retries += 1;
+            tokio::time::sleep(settings.backoff(retries)).await;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        pool: &Pool,
+        settings: &RequestSettings,
+    ) -> Result<ReceivedResponse, SendError> {
+        let mut method = self.method.clone();
+        let mut url = Url::parse(&self.url).map_err(|_| SendError::invalid_url())?;
+        let mut headers = self.headers.clone();
+        let mut body = Some(self.body.clone());
+        let mut redirects = vec![];
+        // Cookies set by the hops are sent to the following hops
+        let mut jar = self.jar.clone();
+        let mut cookies = vec![];
+        let started = Instant::now();
+        // Bytes sent and received over all hops
+        let mut traffic = (0, 0);
+        loop {
+            let content_type = body.is_some().then(|| self.content_type.clone());
+            let hop_started = Instant::now();
+            let (response, first_byte) = http_request(
+                pool,
+                method.clone(),
+                &url,
+                with_cookies(&headers, jar.as_ref(), &url)?,
+                content_type,
+                body.clone().unwrap_or_default(),
+            )
+            .await?;
+            let connection = response.extensions().get::<Arc<ConnectionInfo>>().cloned();
+            let reused = connection.as_ref().is_some_and(|info| info.reuse());
+            let received = cookies::from_response(&url, response.headers());
+            if let Some(jar) = &mut jar {
+                jar.store(&received);
+            }
+            cookies.extend(received);
+
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .map(str::to_string);
+            let location = match location {
+                Some(location) if is_redirect(status) && settings.max_redirects > 0 => location,
+                _ => {
+                    let mut timing = Timing {
+                        redirects: match redirects.is_empty() {
+                            true => Duration::ZERO,
+                            false => hop_started - started,
+                        },
+                        first_byte,
+                        ..Default::default()
+                    };
+                    // Opening the connection is part of waiting for the first byte
+                    if let (Some(info), false) = (&connection, reused) {
+                        timing.dns = info.dns;
+                        timing.connect = info.connect;
+                        timing.tls = info.tls;
+                        timing.first_byte =
+                            first_byte.saturating_sub(info.dns + info.connect + info.tls);
+                    }
+                    let mut response = ReceivedResponse::read(response, url, started, timing)
+                        .await
+                        .map_err(SendError::Other)?;
+                    add_traffic(&mut traffic, &connection);
+                    (response.timing.bytes_sent, response.timing.bytes_received) = traffic;
+                    response.reused = reused;
+                    response.cookies = cookies;
+                    response.redirects = redirects;
+                    return Ok(response);
+                }
+            };
+            if redirects.len() == settings.max_redirects {
+                return Err(SendError::Other(format!(
+                    "Too many redirects, stopped after {}",
+                    settings.max_redirects
+                )));
+            }
+            let from = url;
+            let next = from.join(&location).map_err(|e| {
+                SendError::Other(format!("Invalid redirect location '{location}': {e}"))
+            })?;
+            // Reading the body frees the connection for the next request
+            let _ = hyper::body::to_bytes(response.into_body()).await;
+            add_traffic(&mut traffic, &connection);
+            // Like browsers, redirects of POST requests and 303 redirects change to GET
+            if status == StatusCode::SEE_OTHER && method != Method::HEAD
+                || matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                    && method == Method::POST
+            {
+                method = Method::GET;
+                body = None;
+            }
+            // Credentials are not sent to other origins, cookies of the jar are sent by their
+            // own domain
+            if next.origin() != from.origin() {
+                let credentials = [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION];
+                for name in credentials.into_iter().chain(self.api_key_header.clone()) {
+                    headers.remove(name);
+                }
+            }
+            redirects.push(Redirect {
+                status,
+                url: from.to_string(),
+                location,
+            });
+            url = next;
+        }
+    }
+}
+
+/// `headers` with the cookies of `jar` for `url` added to the `Cookie` header
+fn with_cookies(
+    headers: &HeaderMap,
+    jar: Option<&CookieJar>,
+    url: &Url,
+) -> Result<HeaderMap, SendError> {
+    let mut headers = headers.clone();
+    if let Some(cookies) = jar.and_then(|jar| jar.header_for(url)) {
+        let cookies = match headers.get(COOKIE).and_then(|c| c.to_str().ok()) {
+            Some(existing) => format!("{existing}; {cookies}"),
+            None => cookies,
+        };
+        let value = HeaderValue::from_str(&cookies)
+            .map_err(|e| SendError::Other(format!("Invalid cookie in jar: {e}")))?;
+        headers.insert(COOKIE, value);
+    }
+    Ok(headers)
+}
+
+/// Adds the bytes that went over `connection` since the last time to `traffic`
+fn add_traffic(traffic: &mut (u64, u64), connection: &Option<Arc<ConnectionInfo>>) {
+    if let Some(info) = connection {
+        let (sent, received) = info.take_traffic();
+        traffic.0 += sent;
+        traffic.1 += received;
+    }
+}
+
+fn is_redirect(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    )
+}
+
+/// Why a request failed, connection errors are retried
+#[derive(Debug)]
+pub enum SendError {
+    Connect(String),
+    Other(String),
+}
+
+impl SendError {
+    fn new(error: hyper::Error) -> Self {
+        // The sources tell what went wrong, like a refused connection
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(&error);
+        while let Some(error) = source {
+            // Errors of TLS repeat the message of their source
+            let text = error.to_string();
+            if !message.contains(&text) {
+                message = format!("{message}: {text}");
+            }
+            source = error.source();
+        }
+        if error.is_connect() {
+            Self::Connect(message)
+        } else {
+            Self::Other(message)
+        }
+    }
+
+    fn timed_out(timeout: Duration) -> Self {
+        Self::Other(format!("Timed out after {} ms", timeout.as_millis()))
+    }
+
+    fn invalid_url() -> Self {
+        Self::Other(String::from(
+            "Invalid URI (Make sure to include the url scheme, for example: http://)",
+        ))
+    }
+}
+
+/// A redirect that was followed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub status: StatusCode,
+    pub url: String,
+    pub location: String,
+}
+
+/// A response of which the body has been read completely
+#[derive(Debug, Clone)]
+pub struct ReceivedResponse {
+    pub status: StatusCode,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: String,
+    pub time: Duration,
+    /// Whether the response was received over a connection of an earlier response
+    pub reused: bool,
+    /// Cookies set by the redirects and the response, in the order they were received
+    pub cookies: Vec<Cookie>,
+    /// Redirects that were followed to get this response
+    pub redirects: Vec<Redirect>,
+    /// How often the request was sent again
+    pub retries: u32,
+    pub timing: Timing,
+}
+
+impl ReceivedResponse {
+    /// Reads the body of a response from `url` to a request that was started at `started`, the
+    /// download is added to `timing`
+    pub async fn read(
+        response: Response<Body>,
+        url: Url,
+        started: Instant,
+        mut timing: Timing,
+    ) -> Result<Self, String> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let mut response = response.into_body();
+        let download = Instant::now();
+        let mut body = vec![];
+        while let Some(chunk) = response.data().await {
+            let chunk = chunk.map_err(|e| format!("Failed to read the response body: {e}"))?;
+            body.extend_from_slice(&chunk);
+        }
+        timing.download = download.elapsed();
+        timing.total = started.elapsed();
+        let body = String::from_utf8(body)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+        Ok(Self {
+            status,
+            url,
+            headers,
+            body,
+            time: timing.total,
+            reused: false,
+            cookies: vec![],
+            timing,
+            redirects: vec![],
+            retries: 0,
+        })
+    }
+}
+
+/// Parses a method typed by the user, custom methods like `PROPFIND` have to be a valid token
+pub fn parse_method(name: &str) -> Result<Method, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("Method is empty"));
+    }
+    Method::from_bytes(name.as_bytes()).map_err(|_| format!("Not a valid method: {}", name))
+}
+
+/// Sends a request without following redirects, returns the response once its head arrived
+/// and how long that took
+pub async fn http_request(
+    pool: &Pool,
+    method: Method,
+    url: &Url,
+    mut headers: HeaderMap,
+    content_type: Option<HeaderValue>,
+    body: Vec<u8>,
+) -> Result<(Response<Body>, Duration), SendError> {
+    if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+        return Err(SendError::invalid_url());
+    }
+    let mut url = url.clone();
+    url.set_fragment(None);
+    // Credentials in the URL are sent with basic authentication, like browsers do
+    if !url.username().is_empty() {
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let credentials = format!(
+            "{}:{}",
+            decode(url.username()),
+            decode(url.password().unwrap_or_default())
+        );
+        if let Ok(mut value) =
+            HeaderValue::from_str(&format!("Basic {}", STANDARD.encode(credentials)))
+        {
+            value.set_sensitive(true);
+            headers.entry(AUTHORIZATION).or_insert(value);
+        }
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+    }
+    headers
+        .entry(ACCEPT)
+        .or_insert(HeaderValue::from_static("*/*"));
+    if let Some(content_type) = content_type {
+        headers.insert(CONTENT_TYPE, content_type);
+    }
+
+    let mut request = Request::new(Body::from(body));
+    *request.method_mut() = method;
+    *request.uri_mut() = url.as_str().parse().map_err(|_| SendError::invalid_url())?;
+    *request.headers_mut() = headers;
     let timer = Instant::now();
-    let response = request.send().await?;
+    let response = pool.send(request).await.map_err(SendError::new)?;
     Ok((response, timer.elapsed()))
 }
 
 #[cfg(test)]
 mod tests {
-    use mockito::{self, mock};
-    use reqwest::header::{HeaderMap, HeaderValue};
-    use tokio::test;
+    use mockito::{self, mock, Matcher};
+    use reqwest::{
+        header::{HeaderMap, HeaderValue},
+        Url,
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
 
-    use super::http_request;
+    use super::{apply_auth, header_map, http_request, parse_method, PreparedRequest};
+    use crate::{
+        client::{ClientSettings, HttpClient},
+        collection::{ApiKeyLocation, Auth, KeyValue, SavedRequest},
+        cookies::{Cookie, CookieJar},
+    };
 
-    #[test]
+    /// Serves `response` to every request on the first connection, unlike mockito which closes
+    /// its connections. Returns the URL of the server.
+    async fn keep_alive_server(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut line = String::new();
+            loop {
+                let mut length = 0;
+                // The head of a request ends with an empty line
+                loop {
+                    line.clear();
+                    if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).await.unwrap();
+                stream
+                    .get_mut()
+                    .write_all(response.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
     async fn get_ok() {
         let _mock = mock("GET", "/").create();
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
         let (resp, _time) = http_request(
+            &client.pool(None).unwrap(),
             reqwest::Method::GET,
-            mockito::server_url(),
+            &Url::parse(&mockito::server_url()).unwrap(),
             HeaderMap::new(),
-            HeaderValue::from_str("").unwrap(),
-            "{}",
+            Some(HeaderValue::from_str("").unwrap()),
+            b"{}".to_vec(),
         )
         .await
         .unwrap();
         assert_eq!(resp.status(), 200)
     }
+
+    #[tokio::test]
+    async fn custom_method() {
+        let _mock = mock("PROPFIND", "/custom-method").with_status(207).create();
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let url = format!("{}/custom-method", mockito::server_url());
+        let (resp, _time) = http_request(
+            &client.pool(None).unwrap(),
+            parse_method(" PROPFIND ").unwrap(),
+            &Url::parse(&url).unwrap(),
+            HeaderMap::new(),
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), 207);
+        assert!(parse_method("").is_err());
+        assert!(parse_method("NOT A TOKEN").is_err());
+    }
+
+    #[test]
+    fn header_map_rejects_invalid_names() {
+        let valid = KeyValue::new("x-token", "abc");
+        let invalid = KeyValue {
+            key: String::from("bad header"),
+            ..valid.clone()
+        };
+        assert_eq!(header_map([&valid]).unwrap()["x-token"], "abc");
+        assert!(header_map([&valid, &invalid]).is_err());
+    }
+
+    #[test]
+    fn apply_auth_schemes() {
+        let mut headers = HeaderMap::new();
+        let basic = Auth::Basic {
+            username: String::from("user"),
+            password: String::from("pass"),
+        };
+        apply_auth(&basic, "http://localhost/", &mut headers).unwrap();
+        assert_eq!(headers["authorization"], "Basic dXNlcjpwYXNz");
+
+        let api_key = Auth::ApiKey {
+            key: String::from("api_key"),
+            value: String::from("s3cr3t"),
+            location: ApiKeyLocation::Query,
+        };
+        let url = apply_auth(&api_key, "http://localhost/?a=1", &mut headers).unwrap();
+        assert_eq!(url, "http://localhost/?a=1&api_key=s3cr3t");
+    }
+
+    #[tokio::test]
+    async fn follow_redirects_and_retry() {
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let _moved = mock("POST", "/http/moved")
+            .with_status(302)
+            .with_header("location", "/http/target")
+            .create();
+        let _target = mock("GET", "/http/target").with_body("done").create();
+        let mut request = SavedRequest {
+            method: String::from("POST"),
+            url: format!("{}/http/moved", mockito::server_url()),
+            ..Default::default()
+        };
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.body, "done");
+        assert_eq!(response.redirects.len(), 1);
+        assert_eq!(response.redirects[0].status, 302);
+        assert_eq!(response.redirects[0].location, "/http/target");
+
+        request.settings = vec![KeyValue::new("redirects", "none")];
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.status, 302);
+        assert!(response.redirects.is_empty());
+
+        let flaky = mock("GET", "/http/flaky")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let request = SavedRequest {
+            url: format!("{}/http/flaky", mockito::server_url()),
+            settings: vec![
+                KeyValue::new("retries", "2"),
+                KeyValue::new("retry_backoff", "1"),
+            ],
+            ..Default::default()
+        };
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!((response.status.as_u16(), response.retries), (503, 2));
+        flaky.assert();
+        let timing = &response.timing;
+        assert!(timing.total >= timing.first_byte + timing.download);
+        // `HTTP/1.1 503 Service Unavailable` and the headers of mockito
+        assert!(timing.bytes_received > 32);
+        // `GET /http/flaky HTTP/1.1` and the headers
+        assert!(timing.bytes_sent > 24);
+
+        // The server may have acted on a POST already
+        let failed = mock("POST", "/http/failed")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let mut request = SavedRequest {
+            method: String::from("POST"),
+            url: format!("{}/http/failed", mockito::server_url()),
+            settings: vec![
+                KeyValue::new("retries", "2"),
+                KeyValue::new("retry_backoff", "1"),
+            ],
+            ..Default::default()
+        };
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.retries, 0);
+        failed.assert();
+        drop(failed);
+        let failed = mock("POST", "/http/failed")
+            .with_status(503)
+            .expect(3)
+            .create();
+        request
+            .settings
+            .push(KeyValue::new("retry_non_idempotent", "true"));
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.retries, 2);
+        failed.assert();
+    }
+
+    /// The URL of the mockito server under another origin, cookies of `127.0.0.1` are not
+    /// sent to it
+    fn other_origin(path: &str) -> String {
+        mockito::server_url().replace("127.0.0.1", "localhost") + path
+    }
+
+    #[tokio::test]
+    async fn redirect_cookies() {
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let _login = mock("GET", "/http/login")
+            .with_status(302)
+            .with_header("set-cookie", "session=s3cr3t; Path=/")
+            .with_header("location", "/http/home")
+            .create();
+        let _home = mock("GET", "/http/home")
+            .match_header("cookie", "session=s3cr3t")
+            .with_body("home")
+            .create();
+        let request = SavedRequest {
+            url: format!("{}/http/login", mockito::server_url()),
+            ..Default::default()
+        };
+        let jar = CookieJar {
+            enabled: true,
+            ..Default::default()
+        };
+        let response = PreparedRequest::new(&request, Some(&jar))
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.body, "home");
+        let cookies: Vec<_> = response.cookies.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(cookies, ["session"]);
+    }
+
+    #[tokio::test]
+    async fn redirect_cookies_per_origin() {
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let _moved = mock("GET", "/http/origin")
+            .match_header("cookie", "id=1")
+            .with_status(302)
+            .with_header("location", &other_origin("/http/origin-target"))
+            .create();
+        let _target = mock("GET", "/http/origin-target")
+            .match_header("cookie", "other=2")
+            .with_body("done")
+            .create();
+        let request = SavedRequest {
+            url: format!("{}/http/origin", mockito::server_url()),
+            ..Default::default()
+        };
+        let jar = CookieJar {
+            enabled: true,
+            cookies: vec![
+                Cookie::parse("id=1", &Url::parse(&mockito::server_url()).unwrap()).unwrap(),
+                Cookie::parse("other=2", &Url::parse(&other_origin("/")).unwrap()).unwrap(),
+            ],
+        };
+        let response = PreparedRequest::new(&request, Some(&jar))
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(response.body, "done");
+    }
+
+    #[tokio::test]
+    async fn redirect_strips_api_key() {
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let _same = mock("GET", "/http/key")
+            .match_header("x-api-key", "k3y")
+            .with_status(302)
+            .with_header("location", "/http/key-moved")
+            .create();
+        let _moved = mock("GET", "/http/key-moved")
+            .match_header("x-api-key", "k3y")
+            .with_status(302)
+            .with_header("location", &other_origin("/http/key-target"))
+            .create();
+        let _target = mock("GET", "/http/key-target")
+            .match_header("x-api-key", Matcher::Missing)
+            .with_body("done")
+            .create();
+        let request = SavedRequest {
+            url: format!("{}/http/key", mockito::server_url()),
+            auth: Auth::ApiKey {
+                key: String::from("X-Api-Key"),
+                value: String::from("k3y"),
+                location: ApiKeyLocation::Header,
+            },
+            ..Default::default()
+        };
+        let response = PreparedRequest::new(&request, None)
+            .unwrap()
+            .send(&client)
+            .await
+            .unwrap();
+        assert_eq!(
+            (response.body.as_str(), response.redirects.len()),
+            ("done", 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn reuse_connections() {
+        const RESPONSE: &str = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+        let url = keep_alive_server(RESPONSE).await;
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let request = SavedRequest {
+            url,
+            ..Default::default()
+        };
+        let mut sent = vec![];
+        for reused in [false, true] {
+            let response = PreparedRequest::new(&request, None)
+                .unwrap()
+                .send(&client)
+                .await
+                .unwrap();
+            assert_eq!((response.body.as_str(), response.reused), ("ok", reused));
+            let timing = response.timing;
+            assert_eq!(timing.bytes_received, RESPONSE.len() as u64);
+            assert_eq!(timing.connect.is_zero(), reused);
+            assert!(timing.tls.is_zero());
+            sent.push(timing.bytes_sent);
+        }
+        assert!(sent[0] > 0);
+        assert_eq!(sent[0], sent[1]);
+    }
 }
@@ -1,17 +1,33 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    rc::Rc,
+    str::FromStr,
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use serde::Deserialize;
 
 use crate::app::{Action, Movement};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GlobalKeyAction {
     Quit,
-    Exit,
     ToggleRequestList,
     Send,
     Methods,
+    Environments,
+    RunCollection,
+    RunFolder,
+    Save,
+    Cancel,
+    Help,
+    SwitchTheme,
+    ReloadConfig,
     Other,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NormalKeyAction {
     Exit,
     MoveLeft,
@@ -23,6 +39,27 @@ pub enum NormalKeyAction {
     Accept,
     InsertMode,
     Copy,
+    /// Copy the location of the selection, like the JSON pointer of a node
+    CopyPath,
+    Paste,
+    /// Move to the parent of the selection in a tree
+    Parent,
+    NextSibling,
+    PrevSibling,
+    Search,
+    /// Switch between a case-sensitive and a case-insensitive search
+    ToggleCase,
+    /// Switch between a plain text and a regex search
+    ToggleRegex,
+    PrevMatch,
+    Filter,
+    New,
+    Duplicate,
+    Delete,
+    Toggle,
+    Sort,
+    SwitchView,
+    Clear,
     Other,
 }
 
@@ -38,107 +75,610 @@ impl NormalKeyAction {
     }
 }
 
-impl From<KeyEvent> for GlobalKeyAction {
-    fn from(k: KeyEvent) -> Self {
-        match k {
-            KeyEvent {
-                code: KeyCode::Char('q'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Quit,
+/// An action that keys can be bound to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Global(GlobalKeyAction),
+    Normal(NormalKeyAction),
+}
+
+/// Every action with its name in the config file, its description and its default keys
+const ACTIONS: &[(&str, Binding, &str, &[&str])] = {
+    use Binding::{Global, Normal};
+    use GlobalKeyAction as G;
+    use NormalKeyAction as N;
+    &[
+        ("quit", Global(G::Quit), "Quit", &["q"]),
+        (
+            "toggle_request_list",
+            Global(G::ToggleRequestList),
+            "Toggle request list",
+            &["r"],
+        ),
+        (
+            "save",
+            Global(G::Save),
+            "Save request to collection",
+            &["s"],
+        ),
+        ("send", Global(G::Send), "Send request", &["p"]),
+        (
+            "cancel",
+            Global(G::Cancel),
+            "Cancel request that is being sent",
+            &["esc"],
+        ),
+        ("methods", Global(G::Methods), "Select method", &["m"]),
+        (
+            "environments",
+            Global(G::Environments),
+            "Environments",
+            &["e"],
+        ),
+        (
+            "run_collection",
+            Global(G::RunCollection),
+            "Run all requests of the collection",
+            &["R"],
+        ),
+        (
+            "run_folder",
+            Global(G::RunFolder),
+            "Run the folder of the selected request",
+            &["F"],
+        ),
+        ("help", Global(G::Help), "Show the key bindings", &["?"]),
+        (
+            "switch_theme",
+            Global(G::SwitchTheme),
+            "Switch theme",
+            &["T"],
+        ),
+        (
+            "reload_config",
+            Global(G::ReloadConfig),
+            "Reload the client settings from the config",
+            &["C"],
+        ),
+        ("move_left", Normal(N::MoveLeft), "Left", &["left", "h"]),
+        ("move_right", Normal(N::MoveRight), "Right", &["right", "l"]),
+        ("move_up", Normal(N::MoveUp), "Up", &["up", "k"]),
+        ("move_down", Normal(N::MoveDown), "Down", &["down", "j"]),
+        (
+            "prev_tab",
+            Normal(N::PrevTab),
+            "Previous tab",
+            &["shift+tab"],
+        ),
+        ("next_tab", Normal(N::NextTab), "Next tab", &["tab"]),
+        ("exit", Normal(N::Exit), "Exit", &["esc"]),
+        ("accept", Normal(N::Accept), "Accept", &["enter", "space"]),
+        ("insert_mode", Normal(N::InsertMode), "Edit", &["i"]),
+        (
+            "new",
+            Normal(N::New),
+            "New, or next match of a search",
+            &["n"],
+        ),
+        ("duplicate", Normal(N::Duplicate), "Duplicate", &["d"]),
+        ("delete", Normal(N::Delete), "Delete", &["x"]),
+        ("toggle", Normal(N::Toggle), "Toggle", &["t"]),
+        ("clear", Normal(N::Clear), "Clear", &["c"]),
+        ("copy", Normal(N::Copy), "Copy", &["y"]),
+        (
+            "copy_path",
+            Normal(N::CopyPath),
+            "Copy the path of the selection",
+            &["Y"],
+        ),
+        ("paste", Normal(N::Paste), "Paste", &["P"]),
+        ("sort", Normal(N::Sort), "Change sort order", &["o"]),
+        ("switch_view", Normal(N::SwitchView), "Switch view", &["v"]),
+        ("parent", Normal(N::Parent), "Go to parent node", &["H"]),
+        (
+            "next_sibling",
+            Normal(N::NextSibling),
+            "Go to next sibling",
+            &["J"],
+        ),
+        (
+            "prev_sibling",
+            Normal(N::PrevSibling),
+            "Go to previous sibling",
+            &["K"],
+        ),
+        (
+            "search",
+            Normal(N::Search),
+            "Search in the response",
+            &["/"],
+        ),
+        ("prev_match", Normal(N::PrevMatch), "Previous match", &["N"]),
+        (
+            "toggle_case",
+            Normal(N::ToggleCase),
+            "Toggle case-sensitive search in the prompt",
+            &["alt+c"],
+        ),
+        (
+            "toggle_regex",
+            Normal(N::ToggleRegex),
+            "Toggle regex search in the prompt",
+            &["alt+r"],
+        ),
+        (
+            "filter",
+            Normal(N::Filter),
+            "Filter a JSON response",
+            &["f"],
+        ),
+    ]
+};
+
+/// A key with its modifiers. Shift is part of the key for characters, like `A`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
 
-            KeyEvent {
-                code: KeyCode::Char('r'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::ToggleRequestList,
+impl KeyChord {
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            code => code,
+        };
+        if code == KeyCode::BackTab {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
 
-            KeyEvent {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Send,
+    /// Whether the key types text when it is pressed in an input
+    fn types_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && (self.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+}
 
-            KeyEvent {
-                code: KeyCode::Char('m'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Methods,
+impl From<KeyEvent> for KeyChord {
+    fn from(key_event: KeyEvent) -> Self {
+        Self::new(key_event.code, key_event.modifiers)
+    }
+}
 
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parses keys like `x`, `X`, `ctrl+x`, `alt+enter` or `shift+tab`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None if s == "+" => ("", s),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+        let mut chord_modifiers = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            chord_modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier '{modifier}' in '{s}'")),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key '{key}' in '{s}'")),
+                },
+            },
+        };
+        Ok(Self::new(code, chord_modifiers))
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Keys that are pressed one after the other, like `g g`
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySequence(Vec<KeyChord>);
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(KeyChord::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chords.is_empty() {
+            return Err(String::from("Empty key binding"));
+        }
+        Ok(Self(chords))
+    }
+}
+
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chord) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{chord}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Keys of an action in the config file, a single key sequence or a list of them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigKeys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// What happened with a key that was fed to the keymap
+#[derive(Debug, PartialEq)]
+pub enum Feed {
+    /// The key starts or continues a sequence, more keys are needed
+    Pending,
+    /// The key is handled on its own
+    Key,
+    /// The key completed a sequence that is bound to an action
+    Sequence(Binding),
+}
+
+/// The active key bindings
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Binding)>,
+}
+
+pub type GlobalKeymap = Rc<Keymap>;
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).expect("default key bindings are valid")
+    }
+}
+
+impl Keymap {
+    /// Default bindings, with the actions in `config` rebound. All errors are reported at once.
+    pub fn new(config: &BTreeMap<String, ConfigKeys>) -> Result<Self, String> {
+        let mut errors = vec![];
+        for name in config.keys() {
+            if !ACTIONS.iter().any(|(action, ..)| action == name) {
+                errors.push(format!("Unknown action '{name}'"));
+            }
+        }
+
+        let mut bindings = vec![];
+        for &(name, binding, _, defaults) in ACTIONS {
+            let keys: Vec<&str> = match config.get(name) {
+                Some(ConfigKeys::One(key)) => vec![key],
+                Some(ConfigKeys::Many(keys)) => keys.iter().map(String::as_str).collect(),
+                None => defaults.to_vec(),
+            };
+            for key in keys {
+                match key.parse::<KeySequence>() {
+                    Ok(sequence) => bindings.push((sequence, binding)),
+                    Err(e) => errors.push(format!("{name}: {e}")),
+                }
+            }
+        }
+
+        let keymap = Self { bindings };
+        errors.extend(keymap.conflicts());
+        if errors.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    fn conflicts(&self) -> Vec<String> {
+        let mut errors = vec![];
+        for (i, (sequence, binding)) in self.bindings.iter().enumerate() {
+            if *binding == Binding::Normal(NormalKeyAction::Exit)
+                && (sequence.0.len() > 1 || sequence.0[0].types_text())
+            {
+                errors.push(format!(
+                    "exit: '{sequence}' can't be used, exit also leaves text inputs so it has to be a single key that does not type text"
+                ));
+            }
+            for (other, other_binding) in &self.bindings[i + 1..] {
+                if binding == other_binding {
+                    continue;
+                }
+                let conflict = if sequence == other {
+                    // Cancel only applies while a request is sent, the key is passed on otherwise
+                    let global = |b: &Binding| matches!(b, Binding::Global(_));
+                    let cancel = Binding::Global(GlobalKeyAction::Cancel);
+                    global(binding) == global(other_binding)
+                        || (*binding != cancel && *other_binding != cancel)
+                } else {
+                    sequence.0.starts_with(&other.0) || other.0.starts_with(&sequence.0)
+                };
+                if conflict {
+                    errors.push(format!(
+                        "'{sequence}' bound to {} conflicts with '{other}' bound to {}",
+                        name(*binding),
+                        name(*other_binding)
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Action bound to a single key
+    pub fn lookup(&self, key_event: KeyEvent, global: bool) -> Option<Binding> {
+        if key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+        let chord = KeyChord::from(key_event);
+        self.bindings
+            .iter()
+            .find(|(sequence, binding)| {
+                sequence.0 == [chord] && matches!(binding, Binding::Global(_)) == global
+            })
+            .map(|(_, binding)| *binding)
+    }
+
+    /// Adds a key to the keys pressed so far in `pending` and checks if they form a sequence
+    pub fn feed(&self, pending: &mut Vec<KeyChord>, key_event: KeyEvent) -> Feed {
+        if key_event.kind != KeyEventKind::Press {
+            return Feed::Key;
+        }
+        let chord = KeyChord::from(key_event);
+        let mut keys = std::mem::take(pending);
+        keys.push(chord);
+        // Keys that don't continue a sequence start over
+        if !self.starts_sequence(&keys) {
+            keys = vec![chord];
+        }
+        if let Some((_, binding)) = self
+            .bindings
+            .iter()
+            .find(|(sequence, _)| sequence.0.len() > 1 && sequence.0 == keys)
+        {
+            return Feed::Sequence(*binding);
+        }
+        if self.starts_sequence(&keys) {
+            *pending = keys;
+            Feed::Pending
+        } else {
+            Feed::Key
+        }
+    }
+
+    /// Whether `keys` are the start, or all, of a sequence of more than one key
+    fn starts_sequence(&self, keys: &[KeyChord]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(sequence, _)| sequence.0.len() > 1 && sequence.0.starts_with(keys))
+    }
+
+    /// First key bound to an action, for hints
+    pub fn key(&self, binding: Binding) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(_, b)| *b == binding)
+            .map(|(sequence, _)| sequence.to_string())
+    }
+
+    /// Hint like `v: mode, t: content type` with the first key of each action,
+    /// actions without keys are left out
+    pub fn hint(&self, actions: &[(Binding, &str)]) -> String {
+        actions
+            .iter()
+            .filter_map(|&(binding, text)| Some(format!("{}: {text}", self.key(binding)?)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Actions with their description and keys, for the help
+    pub fn help(&self) -> Vec<(Binding, &'static str, Vec<String>)> {
+        ACTIONS
+            .iter()
+            .map(|&(_, binding, description, _)| {
+                let keys = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, b)| *b == binding)
+                    .map(|(sequence, _)| sequence.to_string())
+                    .collect();
+                (binding, description, keys)
+            })
+            .collect()
+    }
+}
+
+fn name(binding: Binding) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|(_, b, ..)| *b == binding)
+        .map_or("?", |(name, ..)| name)
+}
+
+impl From<Option<Binding>> for GlobalKeyAction {
+    fn from(binding: Option<Binding>) -> Self {
+        match binding {
+            Some(Binding::Global(action)) => action,
             _ => Self::Other,
         }
     }
 }
 
-impl From<KeyEvent> for NormalKeyAction {
-    fn from(k: KeyEvent) -> Self {
-        match k {
-            KeyEvent {
-                code: KeyCode::Esc,
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Exit,
-            KeyEvent {
-                code: KeyCode::Left | KeyCode::Char('h'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::MoveLeft,
-
-            KeyEvent {
-                code: KeyCode::Right | KeyCode::Char('l'),
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-                modifiers: KeyModifiers::NONE,
-            } => Self::MoveRight,
-            KeyEvent {
-                code: KeyCode::Up | KeyCode::Char('k'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::MoveUp,
-            KeyEvent {
-                code: KeyCode::Down | KeyCode::Char('j'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::MoveDown,
-            KeyEvent {
-                code: KeyCode::Char('i'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::InsertMode,
-            KeyEvent {
-                code: KeyCode::Char('y'),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Copy,
-            KeyEvent {
-                code: KeyCode::BackTab,
-                modifiers: KeyModifiers::SHIFT,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::PrevTab,
-            KeyEvent {
-                code: KeyCode::Tab,
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::NextTab,
-            KeyEvent {
-                code: KeyCode::Enter | KeyCode::Char(' '),
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press,
-                state: KeyEventState::NONE,
-            } => Self::Accept,
+impl From<Option<Binding>> for NormalKeyAction {
+    fn from(binding: Option<Binding>) -> Self {
+        match binding {
+            Some(Binding::Normal(action)) => action,
             _ => Self::Other,
         }
     }
 }
+
+/// The action of a key in a text input, keys that type text are typed instead,
+/// like `space` when it is bound to accept
+pub fn input_action(key_event: KeyEvent, binding: Option<Binding>) -> NormalKeyAction {
+    if KeyChord::from(key_event).types_text() {
+        NormalKeyAction::Other
+    } else {
+        NormalKeyAction::from(binding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{
+        input_action, Binding, ConfigKeys, Feed, GlobalKeyAction, KeyChord, Keymap, NormalKeyAction,
+    };
+
+    #[test]
+    fn parse_chords() {
+        let chord = |s: &str| s.parse::<KeyChord>();
+        assert_eq!(
+            chord("ctrl+x"),
+            Ok(KeyChord::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(chord("shift+a"), chord("A"));
+        assert_eq!(chord("shift+tab"), chord("backtab"));
+        assert_eq!(chord("ctrl++").unwrap().to_string(), "Ctrl++");
+        assert_eq!(chord("+").unwrap().to_string(), "+");
+        assert_eq!(chord("alt+Enter").unwrap().to_string(), "Alt+Enter");
+        assert_eq!(chord("F5").unwrap().to_string(), "F5");
+        assert!(chord("hyper+x").is_err());
+        assert!(chord("ctrl+foo").is_err());
+    }
+
+    #[test]
+    fn keymap_from_config() {
+        let config = |entries: &[(&str, &[&str])]| {
+            let config: BTreeMap<_, _> = entries
+                .iter()
+                .map(|(name, keys)| {
+                    let keys = keys.iter().map(|key| key.to_string()).collect();
+                    (name.to_string(), ConfigKeys::Many(keys))
+                })
+                .collect();
+            Keymap::new(&config)
+        };
+        assert!(Keymap::new(&BTreeMap::new()).is_ok());
+
+        let keymap = config(&[("send", &["ctrl+s"]), ("save", &["g s"])]).unwrap();
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            keymap.lookup(key(KeyCode::Char('s'), KeyModifiers::CONTROL), true),
+            Some(Binding::Global(GlobalKeyAction::Send))
+        );
+        assert_eq!(
+            keymap.lookup(key(KeyCode::Char('j'), KeyModifiers::NONE), false),
+            Some(Binding::Normal(NormalKeyAction::MoveDown))
+        );
+        let mut pending = vec![];
+        let g = key(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(keymap.feed(&mut pending, g), Feed::Pending);
+        assert_eq!(
+            keymap.feed(&mut pending, key(KeyCode::Char('s'), KeyModifiers::NONE)),
+            Feed::Sequence(Binding::Global(GlobalKeyAction::Save))
+        );
+        assert_eq!(keymap.feed(&mut pending, g), Feed::Pending);
+        assert_eq!(
+            keymap.feed(&mut pending, key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Feed::Key
+        );
+        assert!(pending.is_empty());
+
+        // Accept is bound to enter and space, only enter confirms a text input
+        let space = key(KeyCode::Char(' '), KeyModifiers::NONE);
+        let enter = key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            keymap.lookup(space, false),
+            Some(Binding::Normal(NormalKeyAction::Accept))
+        );
+        assert_eq!(
+            input_action(space, keymap.lookup(space, false)),
+            NormalKeyAction::Other
+        );
+        assert_eq!(
+            input_action(enter, keymap.lookup(enter, false)),
+            NormalKeyAction::Accept
+        );
+        // Keys with a modifier don't type text, so the search toggles apply in the prompt
+        let alt_c = key(KeyCode::Char('c'), KeyModifiers::ALT);
+        assert_eq!(
+            input_action(alt_c, keymap.lookup(alt_c, false)),
+            NormalKeyAction::ToggleCase
+        );
+
+        // Hints show the rebound keys and leave out actions without keys
+        let keymap = config(&[("switch_view", &["ctrl+v"]), ("toggle", &[])]).unwrap();
+        let hint = keymap.hint(&[
+            (Binding::Normal(NormalKeyAction::SwitchView), "mode"),
+            (Binding::Normal(NormalKeyAction::Toggle), "content type"),
+            (Binding::Normal(NormalKeyAction::New), "new"),
+        ]);
+        assert_eq!(hint, "Ctrl+v: mode, n: new");
+
+        let errors = config(&[
+            ("send", &["q"]),
+            ("copy", &["g"]),
+            ("save", &["g s"]),
+            ("exit", &["x"]),
+            ("sned", &["z"]),
+        ])
+        .unwrap_err();
+        assert_eq!(errors.lines().count(), 5, "{errors}");
+        assert!(errors.contains("Unknown action 'sned'"));
+        assert!(errors.contains("'q' bound to quit conflicts with 'q' bound to send"));
+        assert!(errors.contains("'g s' bound to save conflicts with 'g' bound to copy"));
+        assert!(errors.contains("'x' bound to exit conflicts with 'x' bound to delete"));
+    }
+}
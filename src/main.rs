@@ -1,7 +1,7 @@
-use std::{error::Error, io};
+use std::{error::Error, io, time::Duration};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, Event},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -12,27 +12,119 @@ use tui::{
 };
 
 use app::{App, InputMode};
-use keys::GlobalKeyAction;
-use ui::{theme::Theme, ui};
+use cli::Command;
+use client::HttpClient;
+use collection::{Collection, DEFAULT_COLLECTION};
+use config::Config;
+use crossterm::event::KeyEvent;
+use history::History;
+use keys::{Binding, Feed, GlobalKeyAction, Keymap};
+use runner::Selection;
+use ui::{theme::Themes, ui};
 
 mod app;
+mod body;
+mod cli;
+mod client;
+mod clipboard;
+mod collection;
 mod component;
+mod config;
+mod connector;
+mod cookies;
+mod curl;
+mod environment;
+mod extract;
+mod filter;
+mod highlight;
+mod history;
 mod http;
 mod keys;
 mod pane;
+mod query;
+mod runner;
+mod search;
+mod timing;
 mod ui;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Interactive) => (),
+        Ok(Command::Run(options)) => {
+            let code = match cli::run(&options, &mut io::stdout()).await {
+                Ok(true) => 0,
+                Ok(false) => 1,
+                Err(e) => {
+                    eprintln!("{e}");
+                    2
+                }
+            };
+            std::process::exit(code);
+        }
+        Ok(Command::Test(options)) => {
+            let code = match cli::test(&options, &mut io::stdout()).await {
+                Ok(true) => 0,
+                Ok(false) => 1,
+                Err(e) => {
+                    eprintln!("{e}");
+                    2
+                }
+            };
+            std::process::exit(code);
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    let config = Config::load(Config::path())?;
+    let keymap = match Keymap::new(&config.keys) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("Invalid key bindings in {}:\n{e}", Config::path().display());
+            std::process::exit(2);
+        }
+    };
+    let themes = match Themes::new(&config.themes, config.theme.as_deref()) {
+        Ok(themes) => themes,
+        Err(e) => {
+            eprintln!("Invalid themes in {}:\n{e}", Config::path().display());
+            std::process::exit(2);
+        }
+    };
+    let client = match HttpClient::new(config.client) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!(
+                "Invalid client settings in {}:\n{e}",
+                Config::path().display()
+            );
+            std::process::exit(2);
+        }
+    };
+    let collection = Collection::load(Collection::path_for(DEFAULT_COLLECTION))?;
+    let history = History::load(History::path())?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, DisableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if config.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    } else {
+        execute!(stdout, DisableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let app = App::new(Theme::default());
+    let app = App::new(themes, client, collection, history, keymap);
     let res = run_app(&mut terminal, app).await;
 
     // restore terminal
@@ -51,48 +143,70 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// How often the screen is redrawn while a request is being sent
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App<'_>) -> io::Result<()> {
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
         // TODO: only redraw when an event changed something
-        if let Event::Key(key) = event::read()? {
-            // Global keys
-            if app.active_pane().input_mode() == InputMode::Normal {
-                match GlobalKeyAction::from(key) {
-                    GlobalKeyAction::Quit => return Ok(()),
-                    GlobalKeyAction::ToggleRequestList => app.requests_list.toggle_visible(),
-                    GlobalKeyAction::Send => {
-                        match app.send_request().await {
-                            Ok(res) => {
-                                let (resp, time) = res;
-                                app.right_state.response_state.time = time;
-                                app.right_state.response_state.status_code = resp.status();
-                                if let Ok(data) = resp.text().await {
-                                    if let Ok(value) =
-                                        serde_json::from_str::<serde_json::Value>(&data)
-                                    {
-                                        app.right_state.response_state.response =
-                                            match serde_json::to_string_pretty(&value) {
-                                                Ok(data) => data,
-                                                Err(_) => todo!(),
-                                            }
-                                    } else {
-                                        app.right_state.response_state.response = data
-                                    }
-                                }
-                            }
-                            Err(res) => app.right_state.response_state.response = res,
-                        };
+        // Keep redrawing while sending to animate the spinner and pick up the response
+        let timeout = if app.busy() {
+            TICK_RATE
+        } else {
+            Duration::from_secs(60)
+        };
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) if app.input_mode() == InputMode::Normal => {
+                    let quit = match app.keymap.feed(&mut app.pending_keys, key) {
+                        Feed::Pending => false,
+                        Feed::Key => handle_normal_key(&mut app, key, None),
+                        Feed::Sequence(binding) => handle_normal_key(&mut app, key, Some(binding)),
+                    };
+                    if quit {
+                        return Ok(());
                     }
-                    GlobalKeyAction::Methods => {
-                        app.methods_list.toggle_visible();
-                    }
-                    _ => app.handle_key_event(key),
                 }
-            } else {
-                app.handle_key_event(key)
+                Event::Key(key) => app.handle_key_event(key, None),
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse),
+                _ => (),
             }
         }
+        app.poll_responses();
+    }
+}
+
+/// Handles the global keys and passes the others on to the app, returns whether to quit.
+/// `sequence` is the action of the key sequence that the key completed.
+fn handle_normal_key(app: &mut App, key: KeyEvent, sequence: Option<Binding>) -> bool {
+    match GlobalKeyAction::from(sequence.or_else(|| app.keymap.lookup(key, true))) {
+        GlobalKeyAction::Quit => {
+            app.store_request();
+            app.save_collection();
+            return true;
+        }
+        GlobalKeyAction::Save => {
+            app.store_request();
+            app.save_collection();
+        }
+        GlobalKeyAction::ToggleRequestList => app.requests_list.toggle_visible(),
+        GlobalKeyAction::Send => app.send_request(),
+        GlobalKeyAction::Cancel if app.sending() && !app.methods_list.visible() => {
+            app.cancel_request()
+        }
+        GlobalKeyAction::Cancel if app.running() && !app.methods_list.visible() => app.cancel_run(),
+        GlobalKeyAction::Methods => {
+            app.methods_list.toggle_visible();
+        }
+        GlobalKeyAction::Environments => app.environments_list.toggle_visible(),
+        GlobalKeyAction::RunCollection => app.run_collection(Selection::default()),
+        GlobalKeyAction::RunFolder => app.run_folder(),
+        GlobalKeyAction::Help => app.help.toggle_visible(),
+        GlobalKeyAction::SwitchTheme => app.next_theme(),
+        GlobalKeyAction::ReloadConfig => app.reload_config(),
+        _ => app.handle_key_event(key, sequence),
     }
+    false
 }
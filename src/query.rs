@@ -0,0 +1,132 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::collection::KeyValue;
+
+/// Characters that are escaped in query keys and values
+const QUERY_COMPONENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'`');
+
+/// Splits an url into the part before the query, the query and the fragment (including `#`)
+fn split(url: &str) -> (&str, Option<&str>, &str) {
+    let (rest, fragment) = match url.find('#') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    match rest.split_once('?') {
+        Some((base, query)) => (base, Some(query), fragment),
+        None => (rest, None, fragment),
+    }
+}
+
+fn decode(component: &str) -> String {
+    percent_decode_str(&component.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn encode(component: &str) -> String {
+    utf8_percent_encode(component, QUERY_COMPONENT).to_string()
+}
+
+/// Parses the query parameters of an url, all returned parameters are enabled
+pub fn parse(url: &str) -> Vec<KeyValue> {
+    let (_, query, _) = split(url);
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            KeyValue::new(decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// Replaces the query of `url` with the enabled parameters in `params`
+pub fn with_query(url: &str, params: &[KeyValue]) -> String {
+    let (base, query, fragment) = split(url);
+    // Empty values keep the `=` they were typed with, like `a=`
+    let with_equals: Vec<String> = query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.strip_suffix('='))
+        .filter(|key| !key.contains('='))
+        .map(decode)
+        .collect();
+    let query = params
+        .iter()
+        .filter(|p| p.enabled && !p.key.is_empty())
+        .map(|p| {
+            if p.value.is_empty() && with_equals.contains(&p.key) {
+                format!("{}=", encode(&p.key))
+            } else if p.value.is_empty() {
+                encode(&p.key)
+            } else {
+                format!("{}={}", encode(&p.key), encode(&p.value))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    if query.is_empty() {
+        format!("{base}{fragment}")
+    } else {
+        format!("{base}?{query}{fragment}")
+    }
+}
+
+/// Parameters from `url`, with the disabled parameters of `previous` kept at their position
+pub fn merge(url: &str, previous: &[KeyValue]) -> Vec<KeyValue> {
+    let mut params = parse(url);
+    for (i, param) in previous.iter().enumerate() {
+        if !param.enabled {
+            params.insert(i.min(params.len()), param.clone());
+        }
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, parse, with_query};
+    use crate::collection::KeyValue;
+
+    #[test]
+    fn parse_decodes_repeated_keys() {
+        let params = parse("http://localhost/?tag=a%20b&tag=c+d&flag#top");
+        let pairs: Vec<_> = params
+            .iter()
+            .map(|p| (p.key.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(pairs, [("tag", "a b"), ("tag", "c d"), ("flag", "")]);
+    }
+
+    #[test]
+    fn with_query_skips_disabled_and_encodes() {
+        let disabled = KeyValue {
+            enabled: false,
+            ..KeyValue::new("debug", "1")
+        };
+        let params = [KeyValue::new("q", "a&b=c d+"), disabled];
+        let url = with_query("http://localhost/search?old=1#top", &params);
+        assert_eq!(url, "http://localhost/search?q=a%26b%3Dc%20d%2B#top");
+        assert_eq!(parse(&url)[0].value, "a&b=c d+");
+
+        assert_eq!(
+            with_query("http://localhost/?q=1", &[]),
+            "http://localhost/"
+        );
+        assert_eq!(merge("http://localhost/?q=1", &params)[1].key, "debug");
+
+        let url = "http://localhost/?a=&b&c=1";
+        assert_eq!(with_query(url, &parse(url)), url);
+    }
+}
@@ -0,0 +1,569 @@
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    client::HttpClient,
+    collection::{Collection, KeyValue, SavedRequest},
+    environment::{self, Environment},
+    extract,
+    http::{PreparedRequest, ReceivedResponse},
+};
+
+/// A check on a response. Assertions are stored as key/value rows:
+///
+/// | key           | value                                  |
+/// |---------------|----------------------------------------|
+/// | `status`      | `200`, `200-299` or `2xx`              |
+/// | `header NAME` | regex the value should match, or empty |
+/// | `json PATH`   | expected JSON value or text            |
+/// | `time`        | maximum response time in milliseconds  |
+#[derive(Debug, Clone)]
+pub enum Assertion {
+    Status {
+        min: u16,
+        max: u16,
+    },
+    Header {
+        name: String,
+        pattern: Option<Regex>,
+    },
+    Json {
+        pointer: String,
+        expected: Value,
+    },
+    TimeUnder(Duration),
+}
+
+impl Assertion {
+    pub fn parse(row: &KeyValue) -> Result<Self, String> {
+        let (kind, argument) = row
+            .key
+            .trim()
+            .split_once(' ')
+            .unwrap_or((row.key.trim(), ""));
+        let argument = argument.trim();
+        let value = row.value.trim();
+        match kind {
+            "status" => {
+                let status = |s: &str| {
+                    s.trim()
+                        .parse::<u16>()
+                        .map_err(|_| format!("invalid status '{s}'"))
+                };
+                let (min, max) = if let Some((min, max)) = value.split_once('-') {
+                    (status(min)?, status(max)?)
+                } else if let Some(class) = value.strip_suffix("xx") {
+                    match status(class)? {
+                        class @ 1..=5 => (class * 100, class * 100 + 99),
+                        _ => return Err(format!("invalid status class '{value}'")),
+                    }
+                } else {
+                    let status = status(value)?;
+                    (status, status)
+                };
+                if min > max {
+                    return Err(format!("invalid status range '{value}'"));
+                }
+                Ok(Self::Status { min, max })
+            }
+            "header" if !argument.is_empty() => Ok(Self::Header {
+                name: argument.to_string(),
+                pattern: match value {
+                    "" => None,
+                    value => Some(Regex::new(value).map_err(|e| format!("invalid regex: {e}"))?),
+                },
+            }),
+            "json" if !argument.is_empty() => Ok(Self::Json {
+                pointer: json_pointer(argument)?,
+                // Values that are not valid JSON are compared as text
+                expected: serde_json::from_str(value)
+                    .unwrap_or_else(|_| Value::String(value.to_string())),
+            }),
+            "time" => value
+                .trim_end_matches("ms")
+                .trim()
+                .parse()
+                .map(|ms| Self::TimeUnder(Duration::from_millis(ms)))
+                .map_err(|_| format!("invalid time '{value}'")),
+            "header" => Err(String::from("missing header name")),
+            "json" => Err(String::from("missing JSON path")),
+            _ => Err(String::from(
+                "expected status, header NAME, json PATH or time",
+            )),
+        }
+    }
+
+    /// Checks the assertion, returning why it failed
+    pub fn check(&self, response: &ReceivedResponse) -> Result<(), String> {
+        match self {
+            Self::Status { min, max } => {
+                let status = response.status.as_u16();
+                if (*min..=*max).contains(&status) {
+                    Ok(())
+                } else {
+                    Err(format!("got status {status}"))
+                }
+            }
+            Self::Header { name, pattern } => {
+                let value = response
+                    .headers
+                    .get(name.as_str())
+                    .ok_or_else(|| String::from("header is missing"))?;
+                let value = String::from_utf8_lossy(value.as_bytes());
+                match pattern {
+                    Some(pattern) if !pattern.is_match(&value) => Err(format!("got '{value}'")),
+                    _ => Ok(()),
+                }
+            }
+            Self::Json { pointer, expected } => {
+                let body: Value = serde_json::from_str(&response.body)
+                    .map_err(|_| String::from("body is not JSON"))?;
+                match body.pointer(pointer) {
+                    Some(value) if value == expected => Ok(()),
+                    Some(value) => Err(format!("got {value}")),
+                    None => Err(String::from("path not found")),
+                }
+            }
+            Self::TimeUnder(max) => {
+                if response.time < *max {
+                    Ok(())
+                } else {
+                    Err(format!("took {} ms", response.time.as_millis()))
+                }
+            }
+        }
+    }
+}
+
+/// Validator for the assertions table
+pub fn validate_assertion(row: &KeyValue) -> Option<String> {
+    Assertion::parse(row).err()
+}
+
+/// Converts a JSON path like `$.users[0].name` into a JSON pointer like `/users/0/name`.
+/// Paths starting with `/` are already pointers.
+pub fn json_pointer(path: &str) -> Result<String, String> {
+    if path.starts_with('/') {
+        return Ok(path.to_string());
+    }
+    let invalid = || format!("invalid JSON path '{path}'");
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut pointer = String::new();
+    let mut push = |segment: &str| {
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    };
+    while !rest.is_empty() {
+        if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']').ok_or_else(invalid)?;
+            push(index[..end].trim_matches(|c| c == '\'' || c == '"'));
+            rest = &index[end + 1..];
+        } else {
+            let field = rest.strip_prefix('.').unwrap_or(rest);
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            push(&field[..end]);
+            rest = &field[end..];
+        }
+    }
+    Ok(pointer)
+}
+
+/// Enabled variables of the environment named `env`, or of the active environment
+pub fn variables(collection: &Collection, env: Option<&str>) -> Result<Vec<KeyValue>, String> {
+    let environments = collection
+        .environments
+        .lock()
+        .map_err(|_| String::from("Failed to read environments"))?;
+    match env {
+        Some(name) => environments
+            .list
+            .iter()
+            .find(|env| env.name == name)
+            .map(Environment::variables)
+            .ok_or_else(|| format!("No environment named '{name}'")),
+        None => Ok(environments.variables()),
+    }
+}
+
+/// Sends a saved request of `collection`, keeping received cookies in its jar when it is enabled
+pub async fn send(
+    client: &HttpClient,
+    collection: &Collection,
+    request: &SavedRequest,
+    variables: &[KeyValue],
+) -> Result<ReceivedResponse, String> {
+    let mut unresolved = vec![];
+    let request = environment::interpolate_request(request, variables, &mut unresolved);
+    if !unresolved.is_empty() {
+        return Err(format!("Unresolved variables: {}", unresolved.join(", ")));
+    }
+    let prepared = match collection.cookie_jar.lock() {
+        Ok(jar) => PreparedRequest::new(&request, Some(&jar)),
+        Err(_) => PreparedRequest::new(&request, None),
+    }?;
+    let response = prepared.send(client).await?;
+    if let Ok(mut jar) = collection.cookie_jar.lock() {
+        if jar.enabled {
+            jar.store(&response.cookies);
+        }
+    }
+    Ok(response)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    /// The assertion as it was written, like `status = 200`
+    pub assertion: String,
+    /// Why the assertion failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestResult {
+    pub name: String,
+    pub method: String,
+    pub status: Option<u16>,
+    pub time_ms: u64,
+    /// Set when no response was received
+    pub error: Option<String>,
+    pub assertions: Vec<AssertionResult>,
+}
+
+impl RequestResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.error.is_none())
+    }
+
+    pub fn failures(&self) -> usize {
+        self.assertions.iter().filter(|a| a.error.is_some()).count()
+    }
+}
+
+/// Sends a request and checks its assertions. Values extracted from the response are
+/// set in `variables`, a failed extraction counts as a failed assertion.
+pub async fn run_request(
+    client: &HttpClient,
+    collection: &Collection,
+    request: &SavedRequest,
+    variables: &mut Vec<KeyValue>,
+) -> RequestResult {
+    let mut result = RequestResult {
+        name: request.name.clone(),
+        method: request.method.clone(),
+        status: None,
+        time_ms: 0,
+        error: None,
+        assertions: vec![],
+    };
+    let response = match send(client, collection, request, variables).await {
+        Ok(response) => response,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+    result.status = Some(response.status.as_u16());
+    result.time_ms = response.time.as_millis() as u64;
+    result.assertions = request
+        .assertions
+        .iter()
+        .filter(|row| row.enabled && !row.key.is_empty())
+        .map(|row| AssertionResult {
+            assertion: if row.value.is_empty() {
+                row.key.clone()
+            } else {
+                format!("{} = {}", row.key, row.value)
+            },
+            error: Assertion::parse(row)
+                .and_then(|assertion| assertion.check(&response))
+                .err(),
+        })
+        .collect();
+    for (name, value) in extract::extract(&request.extractions, &response) {
+        let error = match value {
+            Ok(value) => {
+                environment::set_variable(variables, &name, value);
+                None
+            }
+            Err(e) => Some(e),
+        };
+        result.assertions.push(AssertionResult {
+            assertion: format!("extract {name}"),
+            error,
+        });
+    }
+    result
+}
+
+/// Results of running the requests of a collection
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunReport {
+    pub collection: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub time_ms: u64,
+    pub results: Vec<RequestResult>,
+}
+
+impl RunReport {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: collection.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, result: RequestResult) {
+        if result.passed() {
+            self.passed += 1;
+        } else {
+            self.failed += 1;
+        }
+        self.results.push(result);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let errors = self.results.iter().filter(|r| r.error.is_some()).count();
+        xml.push_str(&format!(
+            "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{errors}\" time=\"{:.3}\">\n",
+            xml_escape(&self.collection),
+            self.results.len(),
+            self.failed - errors,
+            self.time_ms as f64 / 1000.0,
+        ));
+        for result in &self.results {
+            xml.push_str(&format!(
+                "  <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&result.name),
+                xml_escape(&self.collection),
+                result.time_ms as f64 / 1000.0,
+            ));
+            if let Some(error) = &result.error {
+                xml.push_str(&format!(
+                    ">\n    <error message=\"{}\"/>\n  </testcase>\n",
+                    xml_escape(error)
+                ));
+            } else if !result.passed() {
+                let failures: Vec<_> = result
+                    .assertions
+                    .iter()
+                    .filter_map(|a| Some(format!("{}: {}", a.assertion, a.error.as_ref()?)))
+                    .collect();
+                xml.push_str(&format!(
+                    ">\n    <failure message=\"{} of {} assertions failed\">{}</failure>\n  </testcase>\n",
+                    failures.len(),
+                    result.assertions.len(),
+                    xml_escape(&failures.join("\n"))
+                ));
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Which requests of a collection are run, every request when nothing is set
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Names of the requests to run
+    pub names: Vec<String>,
+    /// Only run the requests in this folder and its subfolders
+    pub folder: Option<String>,
+}
+
+impl Selection {
+    pub fn folder(folder: &str) -> Self {
+        Self {
+            folder: Some(folder.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn contains(&self, request: &SavedRequest) -> bool {
+        (self.names.is_empty() || self.names.contains(&request.name))
+            && self
+                .folder
+                .as_ref()
+                .is_none_or(|folder| request.in_folder(folder))
+    }
+
+    /// The selected requests of `collection`, in their order in the collection
+    pub fn requests<'c>(&self, collection: &'c Collection) -> Vec<&'c SavedRequest> {
+        collection
+            .requests
+            .iter()
+            .filter(|r| self.contains(r))
+            .collect()
+    }
+}
+
+/// Runs the requests of a collection that `selection` contains in order. `on_result` is called
+/// after each request.
+pub async fn run_collection(
+    client: &HttpClient,
+    collection: &Collection,
+    selection: &Selection,
+    variables: &[KeyValue],
+    mut on_result: impl FnMut(&RequestResult),
+) -> RunReport {
+    let started = Instant::now();
+    let mut report = RunReport::new(&collection.name);
+    // Extracted values are available to the requests that follow
+    let mut variables = variables.to_vec();
+    for request in selection.requests(collection) {
+        let result = run_request(client, collection, request, &mut variables).await;
+        on_result(&result);
+        report.add(result);
+    }
+    report.time_ms = started.elapsed().as_millis() as u64;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+
+    use super::{json_pointer, run_collection, Assertion, Selection};
+    use crate::{
+        client::{ClientSettings, HttpClient},
+        collection::{Collection, KeyValue, SavedRequest},
+    };
+
+    #[test]
+    fn json_paths() {
+        assert_eq!(json_pointer("$.users[0].name").unwrap(), "/users/0/name");
+        assert_eq!(json_pointer("$['a/b'].c").unwrap(), "/a~1b/c");
+        assert_eq!(json_pointer("/a/0").unwrap(), "/a/0");
+        assert!(json_pointer("$..a").is_err());
+    }
+
+    #[test]
+    fn status_assertions() {
+        let status = |value: &str| Assertion::parse(&KeyValue::new("status", value));
+        assert!(matches!(
+            status("4xx"),
+            Ok(Assertion::Status { min: 400, max: 499 })
+        ));
+        assert!(matches!(
+            status("200-204"),
+            Ok(Assertion::Status { min: 200, max: 204 })
+        ));
+        assert!(status("700xx").is_err());
+        assert!(status("0xx").is_err());
+        assert!(status("204-200").is_err());
+    }
+
+    #[tokio::test]
+    async fn run_with_assertions() {
+        let _ok = mock("GET", "/runner/ok")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"user": {"id": 42, "name": "alice"}}"#)
+            .create();
+        let _missing = mock("GET", "/runner/missing").with_status(404).create();
+        let _chained = mock("GET", "/runner/users/42").with_status(200).create();
+
+        let url = |path: &str| format!("{}{path}", mockito::server_url());
+        let mut collection = Collection::default();
+        collection.name = String::from("api");
+        collection.requests = vec![
+            SavedRequest {
+                name: String::from("Ok"),
+                url: url("/runner/ok"),
+                assertions: vec![
+                    KeyValue::new("status", "2xx"),
+                    KeyValue::new("header content-type", "json$"),
+                    KeyValue::new("json $.user.id", "42"),
+                    KeyValue::new("json $.user.name", "alice"),
+                    KeyValue::new("time", "5000"),
+                ],
+                extractions: vec![KeyValue::new("id", "json $.user.id")],
+                ..Default::default()
+            },
+            SavedRequest {
+                name: String::from("Missing"),
+                folder: String::from("users/errors"),
+                url: url("/runner/missing"),
+                assertions: vec![
+                    KeyValue::new("status", "200"),
+                    KeyValue::new("header x-request-id", ""),
+                    KeyValue {
+                        enabled: false,
+                        ..KeyValue::new("status", "404")
+                    },
+                ],
+                ..Default::default()
+            },
+            SavedRequest {
+                name: String::from("Chained"),
+                folder: String::from("users"),
+                url: url("/runner/users/{{id}}"),
+                assertions: vec![KeyValue::new("status", "200")],
+                ..Default::default()
+            },
+        ];
+
+        let client = HttpClient::new(ClientSettings::default()).unwrap();
+        let mut progress = 0;
+        let report = run_collection(&client, &collection, &Selection::default(), &[], |_| {
+            progress += 1
+        })
+        .await;
+        assert_eq!(progress, 3);
+        assert_eq!((report.passed, report.failed), (2, 1));
+        assert!(report.results[0].passed());
+        assert!(report.results[2].passed());
+        let missing = &report.results[1];
+        assert_eq!(missing.failures(), 2);
+        assert_eq!(
+            missing.assertions[0].error.as_deref(),
+            Some("got status 404")
+        );
+
+        let junit = report.to_junit();
+        assert!(junit.contains("tests=\"3\" failures=\"1\" errors=\"0\""));
+        assert!(junit.contains("<failure message=\"2 of 2 assertions failed\">"));
+        assert!(report.to_json().contains("\"passed\": 2"));
+
+        let selection = Selection {
+            names: vec![String::from("Ok")],
+            folder: None,
+        };
+        let only = run_collection(&client, &collection, &selection, &[], |_| ()).await;
+        assert_eq!(only.results.len(), 1);
+
+        assert_eq!(collection.folders(), ["users", "users/errors"]);
+        let folder = run_collection(
+            &client,
+            &collection,
+            &Selection::folder("users"),
+            &[],
+            |_| (),
+        )
+        .await;
+        let names: Vec<&str> = folder.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Missing", "Chained"]);
+        assert!(Selection::folder("user").requests(&collection).is_empty());
+    }
+}
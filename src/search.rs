@@ -0,0 +1,72 @@
+use std::ops::Range;
+
+use regex::RegexBuilder;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    /// Search for a regular expression instead of plain text
+    pub regex: bool,
+}
+
+/// A match of a search, by line and byte range in that line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub line: usize,
+    pub range: Range<usize>,
+}
+
+/// Finds the matches of `query` in every line of `text`. Fails on an invalid regex.
+pub fn find(text: &str, query: &str, options: SearchOptions) -> Result<Vec<Match>, String> {
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid regex: {e}"))?;
+    Ok(text
+        .lines()
+        .enumerate()
+        .flat_map(|(line, text)| {
+            regex
+                .find_iter(text)
+                .filter(|m| !m.range().is_empty())
+                .map(move |m| Match {
+                    line,
+                    range: m.range(),
+                })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, Match, SearchOptions};
+
+    #[test]
+    fn find_matches() {
+        let text = "Alpha beta\nalpha.\nALPHA";
+        let mut options = SearchOptions::default();
+        assert_eq!(find(text, "alpha", options).unwrap().len(), 3);
+
+        options.case_sensitive = true;
+        assert_eq!(
+            find(text, "alpha.", options).unwrap(),
+            [Match {
+                line: 1,
+                range: 0..6
+            }]
+        );
+
+        options.regex = true;
+        assert_eq!(find(text, "a.", options).unwrap().len(), 3);
+        assert_eq!(find(text, "x*", options).unwrap(), []);
+        assert!(find(text, "(", options).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How long the phases of a request took and how many bytes were transferred
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    /// Following redirects before the final request was sent
+    pub redirects: Duration,
+    /// Looking up the address of the host, zero when a connection was reused
+    #[serde(default)]
+    pub dns: Duration,
+    /// Opening the TCP connection, zero when a connection was reused
+    #[serde(default)]
+    pub connect: Duration,
+    /// The TLS handshake, zero for http and when a connection was reused
+    #[serde(default)]
+    pub tls: Duration,
+    /// From sending the request until the headers of the response arrived
+    pub first_byte: Duration,
+    /// Reading the body of the response
+    pub download: Duration,
+    pub total: Duration,
+    /// Bytes written to the connections, including redirects and opening new connections
+    #[serde(alias = "bytes_sent_estimate")]
+    pub bytes_sent: u64,
+    /// Bytes read from the connections, including redirects and opening new connections
+    #[serde(alias = "bytes_received_estimate")]
+    pub bytes_received: u64,
+}
@@ -1,18 +1,32 @@
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
-    text::Spans,
+    text::{Span, Spans},
     widgets::{Block, Borders, Clear, List, ListItem},
     Frame,
 };
 
-use crate::component::Component;
+use crate::{
+    component::Component,
+    keys::{Binding, NormalKeyAction},
+};
 
-use super::app::App;
+use super::app::{App, InputMode};
 
+use environments::EnvironmentsPopup;
+use help::Help;
+use history_list::HistoryPane;
+use report::Report;
+use requests_list::NameEdit;
 use right::Right;
 
+pub mod environments;
+pub mod help;
+pub mod history_list;
+pub mod key_value_table;
 pub mod methods_list;
+pub mod mouse;
+pub mod report;
 pub mod requests_list;
 pub mod right;
 pub mod theme;
@@ -21,33 +35,75 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(if app.requests_list.visible() {
-            [Constraint::Percentage(10), Constraint::Percentage(90)]
+            [Constraint::Percentage(20), Constraint::Percentage(80)]
         } else {
             [Constraint::Max(0), Constraint::Percentage(100)]
         })
         .split(f.size());
 
     if app.requests_list.visible() {
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[0]);
+
         let items: Vec<ListItem> = app
             .requests_list
             .items
             .iter()
-            .map(|&i| ListItem::new(Spans::from(i)))
+            .enumerate()
+            .map(
+                |(i, name)| match app.collection.requests.get(i).map(|r| r.folder.as_str()) {
+                    Some(folder) if !folder.is_empty() => ListItem::new(Spans::from(vec![
+                        Span::styled(format!("{folder}/"), app.theme.placeholder()),
+                        Span::raw(name.as_str()),
+                    ])),
+                    _ => ListItem::new(Spans::from(name.as_str())),
+                },
+            )
             .collect();
 
-        let title = app.requests_list.selected().unwrap_or("None selected");
+        let title = app.collection.name.as_str();
 
         let items = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title(title)
-                    .style(app.theme.block(app.requests_list.active())),
+                    .style(app.theme.block(app.requests_list.active()))
+                    .border_style(app.theme.border(app.requests_list.active())),
             )
             .highlight_symbol("> ")
             .highlight_style(app.theme.selected());
 
-        f.render_stateful_widget(items, chunks[0], &mut app.requests_list.state)
+        app.requests_list.list_area.update(
+            left[0],
+            app.requests_list.state.selected(),
+            app.requests_list.items.len(),
+            1,
+        );
+        f.render_stateful_widget(items, left[0], &mut app.requests_list.state);
+        f.render_stateful_widget(HistoryPane, left[1], &mut app.history_list);
+
+        if app.requests_list.input_mode() == InputMode::Name {
+            let area = centered_rect(40, 20, f.size());
+            let area = Rect {
+                height: area.height.min(3),
+                ..area
+            };
+            app.requests_list.name_input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(match app.requests_list.name_edit {
+                        NameEdit::Name => "Rename",
+                        NameEdit::Folder => "Folder, subfolders separated by /",
+                    })
+                    .style(app.theme.block(true))
+                    .border_style(app.theme.border(true)),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(app.requests_list.name_input.widget(), area);
+        }
     }
 
     let block = Block::default().title("right").borders(Borders::ALL);
@@ -67,14 +123,66 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Methods")
-                    .style(app.theme.block(app.methods_list.active())),
+                    .title(format!(
+                        "Methods ({})",
+                        app.keymap
+                            .hint(&[(Binding::Normal(NormalKeyAction::New), "custom")])
+                    ))
+                    .style(app.theme.block(app.methods_list.active()))
+                    .border_style(app.theme.border(app.methods_list.active())),
             )
             .highlight_symbol("> ")
             .highlight_style(app.theme.selected());
         let area = centered_rect(30, 60, f.size());
+        app.methods_list.list_area.update(
+            area,
+            app.methods_list.state.selected(),
+            app.methods_list.items.len(),
+            1,
+        );
         f.render_widget(Clear, area);
         f.render_stateful_widget(items, area, &mut app.methods_list.state);
+
+        if let Some(input) = &mut app.methods_list.input {
+            let area = Rect {
+                y: area.bottom().saturating_sub(3),
+                height: area.height.min(3),
+                ..area
+            };
+            let title = match &app.methods_list.error {
+                Some(error) => Span::styled(error.clone(), app.theme.error()),
+                None => Span::raw("Custom method"),
+            };
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .style(app.theme.block(true))
+                    .border_style(app.theme.border(true)),
+            );
+            input.set_cursor_style(app.theme.cursor());
+            f.render_widget(Clear, area);
+            f.render_widget(input.widget(), area);
+        }
+    }
+
+    if app.environments_list.visible() {
+        let area = centered_rect(70, 60, f.size());
+        f.render_stateful_widget(
+            EnvironmentsPopup::default(),
+            area,
+            &mut app.environments_list,
+        );
+    }
+
+    if app.report.visible() {
+        let area = centered_rect(70, 70, f.size());
+        f.render_stateful_widget(Report, area, &mut app.report);
+    }
+
+    if app.help.visible() {
+        let area = centered_rect(60, 80, f.size());
+        f.render_stateful_widget(Help, area, &mut app.help);
     }
 }
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
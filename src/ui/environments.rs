@@ -0,0 +1,391 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    app::{Action, InputMode},
+    component::Component,
+    environment::{Environment, Environments},
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    ui::{
+        key_value_table::{KeyValueTable, KeyValueTableState},
+        theme::GlobalTheme,
+    },
+};
+
+/// Popup to switch between and edit the environments of a collection
+pub struct EnvironmentsList<'a> {
+    environments: Arc<Mutex<Environments>>,
+    /// Index 0 is "No environment", the environments follow
+    pub state: ListState,
+    pub variables: KeyValueTableState<'a>,
+    /// Input for the name of a new or renamed environment
+    name_input: Option<TextArea<'a>>,
+    /// Whether `name_input` renames the selected environment instead of adding one
+    renaming: bool,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+    active: bool,
+    visible: bool,
+}
+
+impl<'a> Component for EnvironmentsList<'a> {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if let Some(input) = &mut self.name_input {
+            match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => self.name_input = None,
+                NormalKeyAction::Accept => return self.commit_name(),
+                _ => {
+                    input.input(key_event);
+                }
+            }
+            return None;
+        }
+
+        if self.variables.active() {
+            self.variables.handle_key(key_event, binding);
+            let changed = self.variables.take_changed();
+            if changed {
+                let rows = self.variables.rows.clone();
+                self.edit_selected(|env| env.variables = rows);
+            }
+            return changed.then_some(Action::SaveCollection);
+        }
+
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::Exit => {
+                self.visible = false;
+                None
+            }
+            NormalKeyAction::MoveUp => {
+                self.select(-1);
+                None
+            }
+            NormalKeyAction::MoveDown => {
+                self.select(1);
+                None
+            }
+            NormalKeyAction::Accept => {
+                let name = self.selected().map(|env| env.name);
+                let mut environments = self.environments.lock().ok()?;
+                environments.active = name;
+                Some(Action::SaveCollection)
+            }
+            NormalKeyAction::NextTab | NormalKeyAction::MoveRight => {
+                if self.selected().is_some() {
+                    self.variables.set_active(true);
+                }
+                None
+            }
+            NormalKeyAction::New => {
+                self.renaming = false;
+                self.name_input = Some(TextArea::default());
+                None
+            }
+            NormalKeyAction::InsertMode => {
+                if let Some(env) = self.selected() {
+                    let mut input = TextArea::from([env.name]);
+                    input.move_cursor(CursorMove::End);
+                    self.renaming = true;
+                    self.name_input = Some(input);
+                }
+                None
+            }
+            NormalKeyAction::Duplicate => {
+                let env = self.selected()?;
+                let name = self.unique_name(&format!("{} (copy)", env.name));
+                self.insert(Environment { name, ..env });
+                Some(Action::SaveCollection)
+            }
+            NormalKeyAction::Delete => {
+                let i = self.state.selected()?.checked_sub(1)?;
+                let mut environments = self.environments.lock().ok()?;
+                let removed = environments.list.remove(i);
+                if environments.active.as_ref() == Some(&removed.name) {
+                    environments.active = None;
+                }
+                drop(environments);
+                self.select(0);
+                Some(Action::SaveCollection)
+            }
+            _ => None,
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active
+    }
+
+    /// Global keys are disabled while the popup is open
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        if self.visible {
+            InputMode::Table
+        } else {
+            InputMode::Normal
+        }
+    }
+}
+
+impl<'a> EnvironmentsList<'a> {
+    pub fn new(
+        theme: GlobalTheme,
+        keymap: GlobalKeymap,
+        environments: Arc<Mutex<Environments>>,
+    ) -> Self {
+        Self {
+            environments,
+            state: ListState::default(),
+            variables: KeyValueTableState::new(theme.clone(), keymap.clone(), None),
+            name_input: None,
+            renaming: false,
+            theme,
+            keymap,
+            active: false,
+            visible: false,
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows the popup with the active environment selected
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            let i = self.environments.lock().ok().and_then(|environments| {
+                let active = environments.active.as_ref()?;
+                environments.list.iter().position(|env| &env.name == active)
+            });
+            self.state.select(Some(i.map_or(0, |i| i + 1)));
+            self.load_variables();
+        }
+    }
+
+    /// Names shown in the list, the active environment is marked
+    pub fn items(&self) -> Vec<String> {
+        let environments = match self.environments.lock() {
+            Ok(environments) => environments,
+            Err(_) => return vec![],
+        };
+        let mark = |active: bool| if active { "● " } else { "  " };
+        std::iter::once(format!(
+            "{}No environment",
+            mark(environments.active.is_none())
+        ))
+        .chain(environments.list.iter().map(|env| {
+            format!(
+                "{}{}",
+                mark(environments.active.as_ref() == Some(&env.name)),
+                env.name
+            )
+        }))
+        .collect()
+    }
+
+    fn selected(&self) -> Option<Environment> {
+        let i = self.state.selected()?.checked_sub(1)?;
+        self.environments.lock().ok()?.list.get(i).cloned()
+    }
+
+    fn edit_selected(&mut self, f: impl FnOnce(&mut Environment)) {
+        if let Some(i) = self.state.selected().and_then(|i| i.checked_sub(1)) {
+            if let Some(env) = self
+                .environments
+                .lock()
+                .ok()
+                .as_mut()
+                .and_then(|environments| environments.list.get_mut(i))
+            {
+                f(env);
+            }
+        }
+    }
+
+    fn load_variables(&mut self) {
+        let variables = self.selected().map(|env| env.variables).unwrap_or_default();
+        self.variables.set_rows(variables);
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self
+            .environments
+            .lock()
+            .map_or(0, |environments| environments.list.len()) as isize
+            + 1;
+        let i = match self.state.selected() {
+            Some(i) if offset == 0 => (i as isize).min(len - 1),
+            Some(i) => (i as isize + offset).rem_euclid(len),
+            None => 0,
+        };
+        self.state.select(Some(i as usize));
+        self.load_variables();
+    }
+
+    /// Inserts an environment after the selected one and selects it
+    fn insert(&mut self, env: Environment) {
+        let i = self.state.selected().unwrap_or(0);
+        if let Ok(mut environments) = self.environments.lock() {
+            let i = i.min(environments.list.len());
+            environments.list.insert(i, env);
+        }
+        self.state.select(Some(i + 1));
+        self.load_variables();
+    }
+
+    /// `name`, with a number appended when an environment with that name already exists
+    fn unique_name(&self, name: &str) -> String {
+        let environments = match self.environments.lock() {
+            Ok(environments) => environments,
+            Err(_) => return name.to_string(),
+        };
+        let exists = |name: &str| environments.list.iter().any(|env| env.name == name);
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while exists(&unique) {
+            unique = format!("{name} {n}");
+            n += 1;
+        }
+        unique
+    }
+
+    fn commit_name(&mut self) -> Option<Action> {
+        let name = self.name_input.take()?.into_lines().join("");
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        if self.renaming {
+            let old = self.selected()?.name;
+            if old == name {
+                return None;
+            }
+            let name = self.unique_name(name);
+            let mut environments = self.environments.lock().ok()?;
+            if environments.active.as_ref() == Some(&old) {
+                environments.active = Some(name.clone());
+            }
+            environments.find_mut(&old)?.name = name;
+        } else {
+            let name = self.unique_name(name);
+            self.insert(Environment {
+                name,
+                variables: vec![],
+            });
+        }
+        Some(Action::SaveCollection)
+    }
+}
+
+#[derive(Default)]
+pub struct EnvironmentsPopup<'a> {
+    _marker: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> StatefulWidget for EnvironmentsPopup<'a> {
+    type State = EnvironmentsList<'a>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        Clear.render(area, buf);
+        let theme = state.theme.clone();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Environments")
+            .style(theme.block(true))
+            .border_style(theme.border(true));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(inner);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+            .split(rows[0]);
+
+        let items: Vec<ListItem> = state
+            .items()
+            .into_iter()
+            .map(|i| ListItem::new(Spans::from(i)))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(theme.block(!state.variables.active()))
+                    .border_style(theme.border(!state.variables.active())),
+            )
+            .highlight_style(theme.selected());
+        StatefulWidget::render(list, columns[0], buf, &mut state.state);
+
+        let variables_block = Block::default()
+            .borders(Borders::ALL)
+            .title("Variables")
+            .style(theme.block(state.variables.active()))
+            .border_style(theme.border(state.variables.active()));
+        let variables_area = variables_block.inner(columns[1]);
+        variables_block.render(columns[1], buf);
+        if state.selected().is_some() {
+            StatefulWidget::render(
+                KeyValueTable::default(),
+                variables_area,
+                buf,
+                &mut state.variables,
+            );
+        }
+
+        let normal = Binding::Normal;
+        let hint = if state.variables.active() {
+            state
+                .keymap
+                .hint(&[(normal(NormalKeyAction::Exit), "back to environments")])
+        } else {
+            state.keymap.hint(&[
+                (normal(NormalKeyAction::Accept), "activate"),
+                (normal(NormalKeyAction::NextTab), "edit variables"),
+                (normal(NormalKeyAction::New), "new"),
+                (normal(NormalKeyAction::InsertMode), "rename"),
+                (normal(NormalKeyAction::Duplicate), "duplicate"),
+                (normal(NormalKeyAction::Delete), "delete"),
+            ])
+        };
+        Paragraph::new(Span::styled(hint, theme.placeholder())).render(rows[1], buf);
+
+        if let Some(input) = &mut state.name_input {
+            let area = Rect {
+                height: area.height.min(3),
+                ..columns[0]
+            };
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(if state.renaming {
+                        "Rename"
+                    } else {
+                        "New environment"
+                    })
+                    .style(theme.block(true))
+                    .border_style(theme.border(true)),
+            );
+            input.set_cursor_style(theme.cursor());
+            Clear.render(area, buf);
+            Widget::render(input.widget(), area, buf);
+        }
+    }
+}
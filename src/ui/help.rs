@@ -0,0 +1,130 @@
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    widgets::{Block, Borders, Cell, Clear, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crate::{
+    app::{Action, InputMode},
+    component::Component,
+    keys::{Binding, GlobalKeyAction, Keymap, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+/// Popup listing the active key bindings
+pub struct HelpState {
+    state: TableState,
+    /// Actions with their description and keys
+    bindings: Vec<(Binding, &'static str, Vec<String>)>,
+    /// Keys that close the popup
+    hint: String,
+    theme: GlobalTheme,
+    active: bool,
+    visible: bool,
+}
+
+impl Component for HelpState {
+    fn handle_key(&mut self, _key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if GlobalKeyAction::from(binding) == GlobalKeyAction::Help {
+            self.visible = false;
+            return None;
+        }
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::Exit | NormalKeyAction::Accept => self.visible = false,
+            NormalKeyAction::MoveUp => self.select(-1),
+            NormalKeyAction::MoveDown => self.select(1),
+            _ => (),
+        }
+        None
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active
+    }
+
+    /// Global keys are disabled while the popup is open
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        if self.visible {
+            InputMode::Table
+        } else {
+            InputMode::Normal
+        }
+    }
+}
+
+impl HelpState {
+    pub fn new(theme: GlobalTheme, keymap: &Keymap) -> Self {
+        Self {
+            state: TableState::default(),
+            bindings: keymap.help(),
+            hint: keymap.hint(&[(Binding::Normal(NormalKeyAction::Exit), "close")]),
+            theme,
+            active: false,
+            visible: false,
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+        self.state.select(None);
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.bindings.len() as isize + 2;
+        let i = match self.state.selected() {
+            Some(i) => (i as isize + offset).clamp(0, len - 1),
+            None => 0,
+        };
+        self.state.select(Some(i as usize));
+    }
+}
+
+#[derive(Default)]
+pub struct Help;
+
+impl StatefulWidget for Help {
+    type State = HelpState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let theme = &state.theme;
+        let mut rows = vec![];
+        let mut global = None;
+        for (binding, description, keys) in &state.bindings {
+            let is_global = matches!(binding, Binding::Global(_));
+            if global != Some(is_global) {
+                global = Some(is_global);
+                let section = if is_global { "Global" } else { "Panes" };
+                rows.push(Row::new([Cell::from(section)]).style(theme.focused()));
+            }
+            let keys = if keys.is_empty() {
+                Cell::from("unbound").style(theme.placeholder())
+            } else {
+                Cell::from(keys.join(", "))
+            };
+            rows.push(Row::new([keys, Cell::from(*description)]));
+        }
+
+        Clear.render(area, buf);
+        let table = Table::new(rows)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Keys ({})", state.hint))
+                    .style(theme.block(true))
+                    .border_style(theme.border(true)),
+            )
+            .widths(&[Constraint::Percentage(35), Constraint::Percentage(65)])
+            .highlight_style(theme.selected());
+        StatefulWidget::render(table, area, buf, &mut state.state);
+    }
+}
@@ -0,0 +1,198 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
+};
+
+use crate::{
+    app::{Action, Movement, PaneType},
+    component::Component,
+    history::History,
+    keys::{Binding, NormalKeyAction},
+    pane::Pane,
+    ui::theme::GlobalTheme,
+};
+
+use super::{mouse::ListArea, right::RightStatePane};
+
+/// Rows of an entry in the list
+const ENTRY_HEIGHT: u16 = 2;
+
+/// Pane listing the requests that were sent
+pub struct HistoryList {
+    pub history: History,
+    pub state: ListState,
+    /// Where the list was last rendered
+    pub list_area: ListArea,
+    theme: GlobalTheme,
+    active: bool,
+}
+
+impl Component for HistoryList {
+    fn handle_key(
+        &mut self,
+        _key_event: crossterm::event::KeyEvent,
+        binding: Option<Binding>,
+    ) -> Option<Action> {
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::MoveUp => {
+                self.select(-1);
+                None
+            }
+            NormalKeyAction::MoveDown => {
+                self.select(1);
+                None
+            }
+            NormalKeyAction::NextTab | NormalKeyAction::PrevTab => {
+                Some(Action::MoveRelative(Movement::Up))
+            }
+            NormalKeyAction::Accept => self.state.selected().map(Action::ViewHistory),
+            NormalKeyAction::InsertMode => self.state.selected().map(Action::RestoreHistory),
+            NormalKeyAction::New => self.state.selected().map(Action::AddHistoryToCollection),
+            NormalKeyAction::Delete => {
+                let i = self.state.selected()?;
+                self.history.entries.remove(i);
+                self.select(0);
+                Some(Action::SaveHistory)
+            }
+            NormalKeyAction::Clear => {
+                self.history.entries.clear();
+                self.state.select(None);
+                Some(Action::SaveHistory)
+            }
+            key => key.relative_or_none(),
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if active && self.state.selected().is_none() {
+            self.select(0);
+        }
+    }
+}
+
+impl Pane for HistoryList {
+    fn relative_pane(&self, dir: Movement) -> Option<PaneType> {
+        match dir {
+            Movement::Up => Some(PaneType::RequestList),
+            Movement::Down => None,
+            Movement::Left => Some(PaneType::Right(RightStatePane::Response)),
+            Movement::Right => Some(PaneType::Right(RightStatePane::Request)),
+        }
+    }
+
+    fn active_pane(&mut self, pane: &PaneType) -> &mut dyn Pane {
+        debug_assert!(matches!(pane, PaneType::History));
+
+        self
+    }
+}
+
+impl HistoryList {
+    pub fn new(theme: GlobalTheme, history: History) -> Self {
+        Self {
+            history,
+            state: ListState::default(),
+            list_area: ListArea::default(),
+            theme,
+            active: false,
+        }
+    }
+
+    /// Selects the entry at `row`, returns whether there is one
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        let len = self.history.entries.len();
+        let Some(i) = self.list_area.item_at(column, row, len, ENTRY_HEIGHT) else {
+            return false;
+        };
+        self.state.select(Some(i));
+        true
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.history.entries.len() as isize;
+        if len == 0 {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if offset == 0 => (i as isize).min(len - 1),
+            Some(i) => (i as isize + offset).rem_euclid(len),
+            None => 0,
+        };
+        self.state.select(Some(i as usize));
+    }
+
+    /// Keeps the selection on the same entry after a new one was added at the top
+    pub fn entry_added(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some(i + 1));
+            self.select(0);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct HistoryPane;
+
+impl StatefulWidget for HistoryPane {
+    type State = HistoryList;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let theme = &state.theme;
+        let items: Vec<ListItem> = state
+            .history
+            .entries
+            .iter()
+            .map(|entry| {
+                let status = match &entry.response {
+                    Ok(response) => Span::styled(
+                        format!(" {} ", response.status),
+                        theme.status_code(response.status),
+                    ),
+                    Err(_) => Span::styled(" ERR ", theme.error()),
+                };
+                let path = reqwest::Url::parse(&entry.request.url)
+                    .map(|url| url.path().to_string())
+                    .unwrap_or_else(|_| entry.request.url.clone());
+                ListItem::new(vec![
+                    Spans::from(vec![
+                        status,
+                        Span::raw(format!(" {} {path}", entry.request.method)),
+                    ]),
+                    Spans::from(Span::styled(
+                        format!(
+                            "  {} {} ms {} B",
+                            entry.time_of_day(),
+                            entry.time.as_millis(),
+                            entry.size()
+                        ),
+                        theme.placeholder(),
+                    )),
+                ])
+            })
+            .collect();
+
+        let len = items.len();
+        state
+            .list_area
+            .update(area, state.state.selected(), len, ENTRY_HEIGHT);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("History")
+                    .style(theme.block(state.active))
+                    .border_style(theme.border(state.active)),
+            )
+            .highlight_symbol("> ")
+            .highlight_style(theme.selected());
+        StatefulWidget::render(list, area, buf, &mut state.state);
+    }
+}
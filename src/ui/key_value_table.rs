@@ -0,0 +1,319 @@
+use std::marker::PhantomData;
+
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    app::Action,
+    collection::KeyValue,
+    component::Component,
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+/// Checks a row, returning a message describing why it is invalid
+pub type Validator = fn(&KeyValue) -> Option<String>;
+
+/// Editable table of key/value rows that can each be enabled or disabled
+pub struct KeyValueTableState<'a> {
+    pub rows: Vec<KeyValue>,
+    state: TableState,
+    /// 0 for the key column, 1 for the value column
+    column: usize,
+    /// Input for the cell that is being edited
+    editor: Option<TextArea<'a>>,
+    validator: Option<Validator>,
+    /// Whether the value of a row can be switched to the path of a file
+    files: bool,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+    active: bool,
+    /// Set when a row was changed, cleared by [`KeyValueTableState::take_changed`]
+    changed: bool,
+}
+
+impl<'a> Component for KeyValueTableState<'a> {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if let Some(editor) = &mut self.editor {
+            match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => self.editor = None,
+                NormalKeyAction::Accept => self.commit(),
+                _ => {
+                    editor.input(key_event);
+                }
+            }
+            return None;
+        }
+
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::Exit => self.active = false,
+            NormalKeyAction::MoveUp => self.previous(),
+            NormalKeyAction::MoveDown => self.next(),
+            NormalKeyAction::MoveLeft => self.column = 0,
+            NormalKeyAction::MoveRight => self.column = 1,
+            NormalKeyAction::InsertMode | NormalKeyAction::Accept => self.edit(),
+            NormalKeyAction::New => {
+                self.rows.push(KeyValue::new("", ""));
+                self.state.select(Some(self.rows.len() - 1));
+                self.column = 0;
+                self.changed = true;
+                self.edit();
+            }
+            NormalKeyAction::Duplicate => {
+                if let Some(i) = self.state.selected() {
+                    self.rows.insert(i + 1, self.rows[i].clone());
+                    self.state.select(Some(i + 1));
+                    self.changed = true;
+                }
+            }
+            NormalKeyAction::Delete => {
+                if let Some(i) = self.state.selected() {
+                    self.rows.remove(i);
+                    self.select_in_range();
+                    self.changed = true;
+                }
+            }
+            NormalKeyAction::Toggle => {
+                if let Some(row) = self.selected_mut() {
+                    row.enabled = !row.enabled;
+                    self.changed = true;
+                }
+            }
+            NormalKeyAction::SwitchView if self.files => {
+                if let Some(row) = self.selected_mut() {
+                    row.file = !row.file;
+                    self.changed = true;
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if active {
+            self.select_in_range();
+        } else {
+            self.editor = None;
+        }
+    }
+}
+
+impl<'a> KeyValueTableState<'a> {
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap, validator: Option<Validator>) -> Self {
+        Self {
+            rows: Vec::new(),
+            state: TableState::default(),
+            column: 0,
+            editor: None,
+            validator,
+            files: false,
+            theme,
+            keymap,
+            active: false,
+            changed: false,
+        }
+    }
+
+    pub fn set_rows(&mut self, rows: Vec<KeyValue>) {
+        self.rows = rows;
+        self.editor = None;
+        self.select_in_range();
+    }
+
+    pub fn set_files(&mut self, files: bool) {
+        self.files = files;
+    }
+
+    /// Returns whether the rows were changed since the last call
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn editing(&self) -> bool {
+        self.editor.is_some()
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut KeyValue> {
+        self.state.selected().and_then(|i| self.rows.get_mut(i))
+    }
+
+    fn edit(&mut self) {
+        let column = self.column;
+        if let Some(row) = self.selected_mut() {
+            let text = if column == 0 { &row.key } else { &row.value };
+            let mut editor = TextArea::from([text.as_str()]);
+            editor.move_cursor(CursorMove::End);
+            self.editor = Some(editor);
+        }
+    }
+
+    fn commit(&mut self) {
+        let column = self.column;
+        if let Some(editor) = self.editor.take() {
+            let text = editor.into_lines().join("");
+            if let Some(row) = self.selected_mut() {
+                if column == 0 {
+                    row.key = text;
+                } else {
+                    row.value = text;
+                }
+                self.changed = true;
+            }
+        }
+    }
+
+    fn select_in_range(&mut self) {
+        let selected = match self.state.selected() {
+            _ if self.rows.is_empty() => None,
+            Some(i) => Some(i.min(self.rows.len() - 1)),
+            None => Some(0),
+        };
+        self.state.select(selected);
+    }
+
+    fn next(&mut self) {
+        if !self.rows.is_empty() {
+            let i = self
+                .state
+                .selected()
+                .map_or(0, |i| (i + 1) % self.rows.len());
+            self.state.select(Some(i));
+        }
+    }
+
+    fn previous(&mut self) {
+        if !self.rows.is_empty() {
+            let i = match self.state.selected() {
+                Some(0) | None => self.rows.len() - 1,
+                Some(i) => i - 1,
+            };
+            self.state.select(Some(i));
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct KeyValueTable<'a> {
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> KeyValueTable<'a> {
+    const WIDTHS: &'static [Constraint] = &[
+        Constraint::Length(3),
+        Constraint::Percentage(40),
+        Constraint::Percentage(60),
+    ];
+}
+
+impl<'a> StatefulWidget for KeyValueTable<'a> {
+    type State = KeyValueTableState<'a>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                    Constraint::Length(if state.editing() { 3 } else { 0 }),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let theme = &state.theme;
+        let rows = state.rows.iter().enumerate().map(|(i, row)| {
+            let error = state.validator.and_then(|v| v(row));
+            let style = if error.is_some() && row.enabled {
+                theme.error()
+            } else {
+                theme.block(row.enabled)
+            };
+            let selected = state.active && state.state.selected() == Some(i);
+            let cell = |text: Spans<'static>, column: usize| {
+                if selected && state.column == column {
+                    Cell::from(text).style(theme.selected())
+                } else {
+                    Cell::from(text)
+                }
+            };
+            let value = match state.files && row.file {
+                true => Spans::from(vec![
+                    Span::styled("file: ", theme.placeholder()),
+                    Span::raw(row.value.clone()),
+                ]),
+                false => Spans::from(row.value.clone()),
+            };
+            Row::new([
+                Cell::from(if row.enabled { "[x]" } else { "[ ]" }),
+                cell(Spans::from(row.key.clone()), 0),
+                cell(value, 1),
+            ])
+            .style(style)
+        });
+
+        let table = Table::new(rows)
+            .header(Row::new(["", "Key", "Value"]).style(theme.focused()))
+            .widths(Self::WIDTHS)
+            .highlight_symbol("> ");
+        if state.active {
+            StatefulWidget::render(table, chunks[0], buf, &mut state.state);
+        } else {
+            Widget::render(table, chunks[0], buf);
+        }
+
+        let error = state
+            .state
+            .selected()
+            .and_then(|i| state.rows.get(i))
+            .filter(|row| row.enabled)
+            .and_then(|row| state.validator.and_then(|v| v(row)));
+        let status = match error {
+            Some(error) => Spans::from(Span::styled(error, theme.error())),
+            None if state.rows.is_empty() => {
+                let hint = state
+                    .keymap
+                    .key(Binding::Normal(NormalKeyAction::New))
+                    .map(|key| format!("Press {key} to add a row"))
+                    .unwrap_or_default();
+                Spans::from(Span::styled(hint, theme.placeholder()))
+            }
+            None => Spans::default(),
+        };
+        Paragraph::new(status).render(chunks[1], buf);
+
+        let file = state.files
+            && state
+                .state
+                .selected()
+                .and_then(|i| state.rows.get(i))
+                .is_some_and(|row| row.file);
+        if let Some(editor) = &mut state.editor {
+            editor.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(match (state.column, file) {
+                        (0, _) => "Key",
+                        (_, true) => "File",
+                        (_, false) => "Value",
+                    })
+                    .style(theme.block(true))
+                    .border_style(theme.border(true)),
+            );
+            editor.set_cursor_style(theme.cursor());
+            Widget::render(editor.widget(), chunks[2], buf);
+        }
+    }
+}
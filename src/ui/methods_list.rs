@@ -1,20 +1,53 @@
 use std::sync::{Arc, Mutex};
 
 use tui::widgets::ListState;
+use tui_textarea::TextArea;
 
-use crate::{component::Component, keys::NormalKeyAction};
+use crate::{
+    app::InputMode,
+    component::Component,
+    http::parse_method,
+    keys::{self, Binding, NormalKeyAction},
+};
 
-#[derive(Debug, Default)]
+use super::mouse::ListArea;
+
+#[derive(Default)]
 pub struct MethodsList {
     pub items: Vec<reqwest::Method>,
     pub state: ListState,
+    /// Where the popup was last rendered
+    pub list_area: ListArea,
+    /// Input for a custom method
+    pub input: Option<TextArea<'static>>,
+    /// Why the typed method is not valid
+    pub error: Option<String>,
     active: bool,
     visible: bool,
     selected_method: Arc<Mutex<reqwest::Method>>,
 }
 impl Component for MethodsList {
-    fn handle_key(&mut self, key_event: crossterm::event::KeyEvent) -> Option<crate::app::Action> {
-        match NormalKeyAction::from(key_event) {
+    fn handle_key(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        binding: Option<Binding>,
+    ) -> Option<crate::app::Action> {
+        if let Some(input) = &mut self.input {
+            match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => {
+                    self.input = None;
+                    self.error = None;
+                }
+                NormalKeyAction::Accept => self.commit_input(),
+                _ => {
+                    if input.input(key_event) {
+                        self.error = None;
+                    }
+                }
+            }
+            return None;
+        }
+        match NormalKeyAction::from(binding) {
             NormalKeyAction::Accept | NormalKeyAction::Exit => {
                 match self.selected() {
                     Some(method) => {
@@ -36,7 +69,11 @@ impl Component for MethodsList {
                 self.next();
                 None
             }
-            _ => todo!(),
+            NormalKeyAction::New => {
+                self.input = Some(TextArea::default());
+                None
+            }
+            _ => None,
         }
     }
     fn active(&self) -> bool {
@@ -45,6 +82,15 @@ impl Component for MethodsList {
     fn set_active(&mut self, active: bool) {
         self.active = active
     }
+
+    /// Global keys are disabled while typing a custom method
+    fn input_mode(&self) -> InputMode {
+        if self.input.is_some() {
+            InputMode::Name
+        } else {
+            InputMode::Normal
+        }
+    }
 }
 
 impl MethodsList {
@@ -58,11 +104,47 @@ impl MethodsList {
         Self {
             items,
             state: _state,
+            list_area: ListArea::default(),
+            input: None,
+            error: None,
             visible: false,
             active: false,
             selected_method,
         }
     }
+    /// Selects `method` in the list and makes it the active method of the request, a custom
+    /// method is added to the list
+    pub fn select_method(&mut self, method: reqwest::Method) {
+        let i = match self.items.iter().position(|m| *m == method) {
+            Some(i) => i,
+            None => {
+                self.items.push(method.clone());
+                self.items.len() - 1
+            }
+        };
+        self.state.select(Some(i));
+        if let Ok(mut data) = self.selected_method.lock() {
+            *data = method;
+        }
+    }
+
+    /// Selects the typed method and closes the popup, or shows why it is not valid
+    fn commit_input(&mut self) {
+        let name = match &self.input {
+            Some(input) => input.lines().join(""),
+            None => return,
+        };
+        match parse_method(&name) {
+            Ok(method) => {
+                self.select_method(method);
+                self.input = None;
+                self.error = None;
+                self.visible = false;
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
     pub fn selected(&self) -> Option<reqwest::Method> {
         self.state.selected().map(|i| self.items[i].clone())
     }
@@ -100,6 +182,8 @@ impl MethodsList {
     }
 
     pub fn toggle_visible(&mut self) {
-        self.visible = !self.visible
+        self.visible = !self.visible;
+        self.input = None;
+        self.error = None;
     }
 }
@@ -0,0 +1,91 @@
+use tui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+/// Rows a wheel step scrolls
+pub const SCROLL_ROWS: u16 = 3;
+
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.left() && column < area.right() && row >= area.top() && row < area.bottom()
+}
+
+/// Index of the tab at `column` in tabs rendered in `area`, which has borders, with a one
+/// character divider like `Tabs` of tui draws them
+pub fn tab_at(titles: &[&str], area: Rect, column: u16, row: u16) -> Option<usize> {
+    if !contains(area, column, row) {
+        return None;
+    }
+    let mut start = area.left() + 1;
+    for (i, title) in titles.iter().enumerate() {
+        // A title has a space on both sides, the divider follows
+        let end = start + title.width() as u16 + 2;
+        if column < end {
+            return Some(i);
+        }
+        start = end + 1;
+    }
+    None
+}
+
+/// Where a bordered list was rendered, to find the item under the mouse.
+/// The offset of `ListState` is private, so it is tracked the same way the list scrolls.
+#[derive(Debug, Default)]
+pub struct ListArea {
+    pub area: Rect,
+    offset: usize,
+}
+
+impl ListArea {
+    /// Call before rendering the list in `area`, with items of `item_height` rows
+    pub fn update(&mut self, area: Rect, selected: Option<usize>, len: usize, item_height: u16) {
+        self.area = area;
+        if len == 0 {
+            return;
+        }
+        let fit = (area.height.saturating_sub(2) / item_height).max(1) as usize;
+        let selected = selected.unwrap_or(0).min(len - 1);
+        if selected >= self.offset + fit {
+            self.offset = selected + 1 - fit;
+        } else if selected < self.offset {
+            self.offset = selected;
+        }
+    }
+
+    pub fn item_at(&self, column: u16, row: u16, len: usize, item_height: u16) -> Option<usize> {
+        let inner = Rect {
+            x: self.area.x + 1,
+            y: self.area.y + 1,
+            width: self.area.width.saturating_sub(2),
+            height: self.area.height.saturating_sub(2),
+        };
+        if !contains(inner, column, row) {
+            return None;
+        }
+        let i = self.offset + ((row - inner.y) / item_height) as usize;
+        (i < len).then_some(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_tabs_and_items() {
+        let area = Rect::new(10, 5, 40, 3);
+        // " Body │ Headers │"
+        assert_eq!(tab_at(&["Body", "Headers"], area, 11, 6), Some(0));
+        assert_eq!(tab_at(&["Body", "Headers"], area, 16, 6), Some(0));
+        assert_eq!(tab_at(&["Body", "Headers"], area, 18, 6), Some(1));
+        assert_eq!(tab_at(&["Body", "Headers"], area, 40, 6), None);
+        assert_eq!(tab_at(&["Body", "Headers"], area, 11, 9), None);
+
+        let mut list = ListArea::default();
+        list.update(Rect::new(0, 0, 20, 5), Some(4), 6, 1);
+        assert_eq!(list.item_at(1, 1, 6, 1), Some(2));
+        assert_eq!(list.item_at(1, 3, 6, 1), Some(4));
+        assert_eq!(list.item_at(0, 1, 6, 1), None);
+        list.update(Rect::new(0, 0, 20, 6), Some(0), 6, 2);
+        assert_eq!(list.item_at(1, 2, 6, 2), Some(0));
+        assert_eq!(list.item_at(1, 3, 6, 2), Some(1));
+    }
+}
@@ -0,0 +1,204 @@
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::{Action, InputMode},
+    component::Component,
+    keys::{Binding, GlobalKeymap, NormalKeyAction},
+    runner::{RequestResult, RunReport},
+    ui::theme::GlobalTheme,
+};
+
+/// Popup with the results of running a collection
+pub struct ReportState {
+    pub report: RunReport,
+    /// Number of requests that are run
+    total: usize,
+    running: bool,
+    cancelled: bool,
+    state: ListState,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+    active: bool,
+    visible: bool,
+}
+
+impl Component for ReportState {
+    fn handle_key(&mut self, _key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::Exit | NormalKeyAction::Accept => self.visible = false,
+            NormalKeyAction::MoveUp => self.select(-1),
+            NormalKeyAction::MoveDown => self.select(1),
+            NormalKeyAction::Delete if self.running => return Some(Action::CancelRun),
+            _ => (),
+        }
+        None
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active
+    }
+
+    /// Global keys are disabled while the popup is open
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        if self.visible {
+            InputMode::Table
+        } else {
+            InputMode::Normal
+        }
+    }
+}
+
+impl ReportState {
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap) -> Self {
+        Self {
+            report: RunReport::default(),
+            total: 0,
+            running: false,
+            cancelled: false,
+            state: ListState::default(),
+            theme,
+            keymap,
+            active: false,
+            visible: false,
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+    }
+
+    /// Clears the report and shows the popup for a new run of `total` requests
+    pub fn start(&mut self, collection: &str, total: usize) {
+        self.report = RunReport::new(collection);
+        self.total = total;
+        self.running = true;
+        self.cancelled = false;
+        self.visible = true;
+        self.state.select(None);
+    }
+
+    pub fn add(&mut self, result: RequestResult) {
+        self.report.add(result);
+    }
+
+    pub fn finish(&mut self, report: RunReport) {
+        self.report = report;
+        self.running = false;
+    }
+
+    /// Stops the report after the results received so far
+    pub fn cancel(&mut self) {
+        self.running = false;
+        self.cancelled = true;
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.report.results.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => (i as isize + offset).rem_euclid(len),
+            None => 0,
+        };
+        self.state.select(Some(i as usize));
+    }
+}
+
+#[derive(Default)]
+pub struct Report;
+
+impl StatefulWidget for Report {
+    type State = ReportState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let theme = &state.theme;
+        let report = &state.report;
+        let mut title = format!(
+            "Run {}: {} passed, {} failed",
+            report.collection, report.passed, report.failed
+        );
+        if state.running {
+            let cancel = state
+                .keymap
+                .hint(&[(Binding::Normal(NormalKeyAction::Delete), "cancel")]);
+            title.push_str(&format!(
+                " (running {}/{}, {cancel})",
+                report.results.len() + 1,
+                state.total
+            ));
+        } else if state.cancelled {
+            title.push_str(&format!(
+                " (cancelled after {}/{})",
+                report.results.len(),
+                state.total
+            ));
+        } else {
+            title.push_str(&format!(" in {} ms", report.time_ms));
+        }
+
+        let items: Vec<ListItem> = report
+            .results
+            .iter()
+            .map(|result| {
+                let mark = if result.passed() {
+                    Span::styled(" PASS ", theme.status_code(200))
+                } else {
+                    Span::styled(" FAIL ", theme.status_code(500))
+                };
+                let status = result.status.map_or(String::new(), |s| s.to_string());
+                let mut lines = vec![Spans::from(vec![
+                    mark,
+                    Span::raw(format!(" {} {} ", result.method, result.name)),
+                    Span::styled(
+                        format!("{status} {} ms", result.time_ms),
+                        theme.placeholder(),
+                    ),
+                ])];
+                if let Some(error) = &result.error {
+                    lines.push(Spans::from(Span::styled(
+                        format!("    {error}"),
+                        theme.error(),
+                    )));
+                }
+                if result.failures() > 0 {
+                    lines.extend(result.assertions.iter().filter_map(|a| {
+                        Some(Spans::from(Span::styled(
+                            format!("    {}: {}", a.assertion, a.error.as_ref()?),
+                            theme.error(),
+                        )))
+                    }));
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+
+        Clear.render(area, buf);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .style(theme.block(true))
+                    .border_style(theme.border(true)),
+            )
+            .highlight_symbol("> ")
+            .highlight_style(theme.selected());
+        StatefulWidget::render(list, area, buf, &mut state.state);
+    }
+}
@@ -1,41 +1,91 @@
 use crossterm::event::KeyEvent;
 use tui::widgets::ListState;
+use tui_textarea::{CursorMove, TextArea};
 
 use crate::{
-    app::{Action, Movement, PaneType},
+    app::{Action, InputMode, Movement, PaneType},
     component::Component,
-    keys::NormalKeyAction,
+    keys::{self, Binding, NormalKeyAction},
     pane::Pane,
 };
 
-use super::right::RightStatePane;
+use super::{mouse::ListArea, right::RightStatePane};
 
-#[derive(Debug, Default)]
-pub struct RequestsList<T: Copy> {
+/// What the name input of the list edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameEdit {
+    Name,
+    Folder,
+}
+
+pub struct RequestsList<'a, T: Clone> {
     pub items: Vec<T>,
     pub state: ListState,
+    /// Input used while renaming the selected item or changing its folder
+    pub name_input: TextArea<'a>,
+    pub name_edit: NameEdit,
+    /// Where the list was last rendered
+    pub list_area: ListArea,
+    input_mode: InputMode,
     active: bool,
     visible: bool,
 }
 
-impl<T: Copy> Component for RequestsList<T> {
-    fn handle_key(&mut self, key_event: KeyEvent) -> Option<Action> {
-        match NormalKeyAction::from(key_event) {
+impl<'a, T: Clone + AsRef<str>> Component for RequestsList<'a, T> {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if self.input_mode == InputMode::Name {
+            return match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => {
+                    self.input_mode = InputMode::Normal;
+                    None
+                }
+                NormalKeyAction::Accept => {
+                    self.input_mode = InputMode::Normal;
+                    let name = self.name_input.clone().into_lines().join("");
+                    match (self.state.selected(), self.name_edit) {
+                        (Some(i), NameEdit::Name) if !name.trim().is_empty() => {
+                            Some(Action::RenameRequest(i, name.trim().to_string()))
+                        }
+                        (Some(i), NameEdit::Folder) => Some(Action::MoveToFolder(i, name)),
+                        _ => None,
+                    }
+                }
+                _ => {
+                    self.name_input.input(key_event);
+                    None
+                }
+            };
+        }
+
+        match NormalKeyAction::from(binding) {
             NormalKeyAction::Accept | NormalKeyAction::MoveRight => {
                 Some(Action::MoveRelative(Movement::Right))
             }
             NormalKeyAction::MoveUp => {
                 self.previous();
-                None
+                self.state.selected().map(Action::SelectRequest)
             }
             NormalKeyAction::MoveDown => {
                 self.next();
-                None
+                self.state.selected().map(Action::SelectRequest)
             }
             NormalKeyAction::PrevTab => {
                 self.next();
+                self.state.selected().map(Action::SelectRequest)
+            }
+            NormalKeyAction::NextTab => Some(Action::MoveRelative(Movement::Down)),
+            NormalKeyAction::New => Some(Action::NewRequest),
+            NormalKeyAction::Duplicate => self.state.selected().map(Action::DuplicateRequest),
+            NormalKeyAction::Delete => self.state.selected().map(Action::DeleteRequest),
+            NormalKeyAction::Copy => self.state.selected().map(Action::ExportCurl),
+            NormalKeyAction::Paste => Some(Action::ImportCurl),
+            NormalKeyAction::InsertMode => {
+                if let Some(name) = self.selected() {
+                    self.edit(NameEdit::Name, name.as_ref());
+                }
                 None
             }
+            NormalKeyAction::Filter => self.state.selected().map(Action::EditFolder),
             key => key.relative_or_none(),
         }
     }
@@ -47,13 +97,18 @@ impl<T: Copy> Component for RequestsList<T> {
     fn set_active(&mut self, active: bool) {
         self.active = active
     }
+
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        self.input_mode
+    }
 }
 
-impl<T: Copy> Pane for RequestsList<T> {
+impl<'a, T: Clone + AsRef<str>> Pane for RequestsList<'a, T> {
     fn relative_pane(&self, dir: crate::app::Movement) -> Option<PaneType> {
         match dir {
             Movement::Up => None,
-            Movement::Down => None,
+            Movement::Down => Some(PaneType::History),
             // NOTE: Is left and right not both RequestList
             Movement::Left => Some(PaneType::Right(RightStatePane::Response)),
             Movement::Right => Some(PaneType::Right(RightStatePane::Request)),
@@ -67,27 +122,57 @@ impl<T: Copy> Pane for RequestsList<T> {
     }
 }
 
-impl<T: Copy> RequestsList<T> {
+impl<'a, T: Clone> RequestsList<'a, T> {
     pub fn new(items: Vec<T>) -> Self {
+        let mut state = ListState::default();
+        if !items.is_empty() {
+            state.select(Some(0));
+        }
         Self {
             items,
-            state: ListState::default(),
+            state,
+            name_input: TextArea::default(),
+            name_edit: NameEdit::Name,
+            list_area: ListArea::default(),
+            input_mode: InputMode::Normal,
             visible: true,
             active: false,
         }
     }
 
+    /// Opens the name input with `value`, for the selected item
+    pub fn edit(&mut self, edit: NameEdit, value: &str) {
+        self.name_input = TextArea::from([value]);
+        self.name_input.move_cursor(CursorMove::End);
+        self.name_edit = edit;
+        self.input_mode = InputMode::Name;
+    }
+
+    /// Replaces the items, keeping the selection when it is still in range
     pub fn set_items(&mut self, items: Vec<T>) {
         self.items = items;
-        // Reset state for selection and offset
-        self.state = ListState::default();
+        let selected = match self.state.selected() {
+            _ if self.items.is_empty() => None,
+            Some(i) => Some(i.min(self.items.len() - 1)),
+            None => Some(0),
+        };
+        self.state.select(selected);
     }
 
     pub fn selected(&self) -> Option<T> {
-        self.state.selected().map(|i| self.items[i])
+        self.state.selected().map(|i| self.items[i].clone())
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.state.select(Some(index));
+        }
     }
 
     pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -102,6 +187,9 @@ impl<T: Copy> RequestsList<T> {
     }
 
     pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -9,15 +9,23 @@ use tui::{
     widgets::StatefulWidget,
 };
 
-use crate::{app::PaneType, component::Component, pane::Pane};
+use crate::{
+    app::PaneType, component::Component, cookies::CookieJar, environment::Environments,
+    keys::GlobalKeymap, pane::Pane,
+};
 
 use super::theme::GlobalTheme;
 
 use request::{Request, RequestState};
 use response::{Response, ResponseState};
 
+mod auth;
+mod cookies;
+mod filter;
+mod json_tree;
 mod request;
 mod response;
+mod search;
 
 pub struct RightState<'a> {
     pub active: bool,
@@ -39,10 +47,21 @@ pub struct Right<'a> {
 }
 
 impl<'a> RightState<'a> {
-    pub fn new(theme: GlobalTheme, selected_method: Arc<Mutex<reqwest::Method>>) -> Self {
+    pub fn new(
+        theme: GlobalTheme,
+        keymap: GlobalKeymap,
+        selected_method: Arc<Mutex<reqwest::Method>>,
+        cookie_jar: Arc<Mutex<CookieJar>>,
+        environments: Arc<Mutex<Environments>>,
+    ) -> Self {
         Self {
-            request_state: RequestState::new(theme.clone(), selected_method),
-            response_state: ResponseState::new(theme),
+            request_state: RequestState::new(
+                theme.clone(),
+                keymap.clone(),
+                selected_method,
+                environments,
+            ),
+            response_state: ResponseState::new(theme, keymap, cookie_jar),
             active: false,
         }
     }
@@ -58,12 +77,7 @@ impl<'a> StatefulWidget for Right<'a> {
             .split(area);
 
         StatefulWidget::render(Request::default(), chunks[0], buf, &mut state.request_state);
-        StatefulWidget::render(
-            Response::default(),
-            chunks[1],
-            buf,
-            &mut state.response_state,
-        );
+        StatefulWidget::render(Response, chunks[1], buf, &mut state.response_state);
     }
 }
 
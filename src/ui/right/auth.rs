@@ -0,0 +1,348 @@
+use std::marker::PhantomData;
+
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    app::Action,
+    collection::{ApiKeyLocation, Auth},
+    component::Component,
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+/// A single editable field of an auth scheme
+struct Field {
+    label: &'static str,
+    secret: bool,
+}
+
+/// Editor for the authentication scheme of a request
+pub struct AuthState<'a> {
+    /// Last used value of every scheme, so switching back and forth keeps the fields
+    schemes: [Auth; 4],
+    scheme_index: usize,
+    state: TableState,
+    editor: Option<TextArea<'a>>,
+    /// Show secret fields in plain text
+    revealed: bool,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+    active: bool,
+}
+
+impl<'a> Component for AuthState<'a> {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if let Some(editor) = &mut self.editor {
+            match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => self.editor = None,
+                NormalKeyAction::Accept => self.commit(),
+                _ => {
+                    editor.input(key_event);
+                }
+            }
+            return None;
+        }
+
+        let row = self.state.selected().unwrap_or(0);
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::Exit => self.active = false,
+            NormalKeyAction::MoveUp => {
+                self.state
+                    .select(Some(row.checked_sub(1).unwrap_or(self.rows() - 1)));
+            }
+            NormalKeyAction::MoveDown => self.state.select(Some((row + 1) % self.rows())),
+            NormalKeyAction::MoveLeft if row == 0 => {
+                self.scheme_index = self.scheme_index.checked_sub(1).unwrap_or(3);
+            }
+            NormalKeyAction::MoveRight if row == 0 => {
+                self.scheme_index = (self.scheme_index + 1) % 4;
+            }
+            NormalKeyAction::MoveLeft | NormalKeyAction::MoveRight | NormalKeyAction::Accept
+                if row == 3 && matches!(self.auth(), Auth::ApiKey { .. }) =>
+            {
+                if let Auth::ApiKey { location, .. } = self.auth_mut() {
+                    *location = match location {
+                        ApiKeyLocation::Header => ApiKeyLocation::Query,
+                        ApiKeyLocation::Query => ApiKeyLocation::Header,
+                    };
+                }
+            }
+            NormalKeyAction::InsertMode | NormalKeyAction::Accept => {
+                if let Some(value) = row.checked_sub(1).and_then(|i| self.value_mut(i)) {
+                    let mut editor = TextArea::from([value.as_str()]);
+                    editor.move_cursor(CursorMove::End);
+                    self.editor = Some(editor);
+                }
+            }
+            NormalKeyAction::Toggle => self.revealed = !self.revealed,
+            _ => (),
+        }
+        None
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if active {
+            self.state.select(Some(0));
+        } else {
+            self.editor = None;
+        }
+    }
+}
+
+impl<'a> AuthState<'a> {
+    const SCHEMES: [&'static str; 4] = ["None", "Basic", "Bearer token", "API key"];
+
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap) -> Self {
+        Self {
+            schemes: Self::empty_schemes(),
+            scheme_index: 0,
+            state: TableState::default(),
+            editor: None,
+            revealed: false,
+            theme,
+            keymap,
+            active: false,
+        }
+    }
+
+    fn empty_schemes() -> [Auth; 4] {
+        [
+            Auth::None,
+            Auth::Basic {
+                username: String::new(),
+                password: String::new(),
+            },
+            Auth::Bearer {
+                token: String::new(),
+            },
+            Auth::ApiKey {
+                key: String::new(),
+                value: String::new(),
+                location: ApiKeyLocation::Header,
+            },
+        ]
+    }
+
+    pub fn auth(&self) -> &Auth {
+        &self.schemes[self.scheme_index]
+    }
+
+    pub fn set_auth(&mut self, auth: Auth) {
+        self.schemes = Self::empty_schemes();
+        self.scheme_index = match auth {
+            Auth::None => 0,
+            Auth::Basic { .. } => 1,
+            Auth::Bearer { .. } => 2,
+            Auth::ApiKey { .. } => 3,
+        };
+        self.schemes[self.scheme_index] = auth;
+        self.editor = None;
+    }
+
+    fn auth_mut(&mut self) -> &mut Auth {
+        &mut self.schemes[self.scheme_index]
+    }
+
+    fn fields(&self) -> &'static [Field] {
+        match self.auth() {
+            Auth::None => &[],
+            Auth::Basic { .. } => &[
+                Field {
+                    label: "Username",
+                    secret: false,
+                },
+                Field {
+                    label: "Password",
+                    secret: true,
+                },
+            ],
+            Auth::Bearer { .. } => &[Field {
+                label: "Token",
+                secret: true,
+            }],
+            Auth::ApiKey { .. } => &[
+                Field {
+                    label: "Key",
+                    secret: false,
+                },
+                Field {
+                    label: "Value",
+                    secret: true,
+                },
+            ],
+        }
+    }
+
+    fn values(&self) -> Vec<&str> {
+        match self.auth() {
+            Auth::None => vec![],
+            Auth::Basic { username, password } => vec![username, password],
+            Auth::Bearer { token } => vec![token],
+            Auth::ApiKey { key, value, .. } => vec![key, value],
+        }
+    }
+
+    fn value_mut(&mut self, index: usize) -> Option<&mut String> {
+        match (self.auth_mut(), index) {
+            (Auth::Basic { username, .. }, 0) => Some(username),
+            (Auth::Basic { password, .. }, 1) => Some(password),
+            (Auth::Bearer { token }, 0) => Some(token),
+            (Auth::ApiKey { key, .. }, 0) => Some(key),
+            (Auth::ApiKey { value, .. }, 1) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Number of rows: the scheme selector, the fields and the api key location
+    fn rows(&self) -> usize {
+        let location = matches!(self.auth(), Auth::ApiKey { .. }) as usize;
+        1 + self.fields().len() + location
+    }
+
+    fn commit(&mut self) {
+        let row = self.state.selected().unwrap_or(0);
+        if let Some(editor) = self.editor.take() {
+            let text = editor.into_lines().join("");
+            if let Some(value) = row.checked_sub(1).and_then(|i| self.value_mut(i)) {
+                *value = text;
+            }
+        }
+    }
+
+    fn editing_secret(&self) -> bool {
+        let row = self.state.selected().unwrap_or(0);
+        row.checked_sub(1)
+            .and_then(|i| self.fields().get(i))
+            .is_some_and(|field| field.secret)
+    }
+}
+
+#[derive(Default)]
+pub struct AuthEditor<'a> {
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> AuthEditor<'a> {
+    const WIDTHS: &'static [Constraint] = &[Constraint::Length(10), Constraint::Percentage(100)];
+
+    /// Hides a secret behind a placeholder of a fixed width, so that neither its length nor
+    /// its words show
+    fn mask(value: &str) -> String {
+        match value.is_empty() {
+            true => String::new(),
+            false => "•".repeat(8),
+        }
+    }
+}
+
+impl<'a> StatefulWidget for AuthEditor<'a> {
+    type State = AuthState<'a>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                    Constraint::Length(if state.editor.is_some() { 3 } else { 0 }),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let theme = &state.theme;
+        let schemes = AuthState::SCHEMES
+            .iter()
+            .enumerate()
+            .flat_map(|(i, scheme)| {
+                let style = if i == state.scheme_index {
+                    theme.selected()
+                } else {
+                    theme.block(false)
+                };
+                [Span::styled(*scheme, style), Span::raw(" | ")]
+            })
+            .take(AuthState::SCHEMES.len() * 2 - 1)
+            .collect::<Vec<_>>();
+
+        let mut rows = vec![Row::new([
+            Cell::from("Type"),
+            Cell::from(Spans::from(schemes)),
+        ])];
+        for (field, value) in state.fields().iter().zip(state.values()) {
+            let value = if field.secret && !state.revealed {
+                AuthEditor::mask(value)
+            } else {
+                value.to_string()
+            };
+            rows.push(Row::new([Cell::from(field.label), Cell::from(value)]));
+        }
+        if let Auth::ApiKey { location, .. } = state.auth() {
+            let location = match location {
+                ApiKeyLocation::Header => "Header",
+                ApiKeyLocation::Query => "Query parameter",
+            };
+            rows.push(Row::new([Cell::from("Add to"), Cell::from(location)]));
+        }
+
+        let table = Table::new(rows)
+            .style(theme.focused())
+            .widths(Self::WIDTHS)
+            .highlight_symbol("> ")
+            .highlight_style(theme.selected());
+        if state.active {
+            StatefulWidget::render(table, chunks[0], buf, &mut state.state);
+        } else {
+            Widget::render(table, chunks[0], buf);
+        }
+
+        let hint = state.keymap.hint(&[
+            (Binding::Normal(NormalKeyAction::MoveLeft), "previous type"),
+            (Binding::Normal(NormalKeyAction::MoveRight), "next type"),
+            (
+                Binding::Normal(NormalKeyAction::Toggle),
+                if state.revealed {
+                    "hide secrets"
+                } else {
+                    "reveal secrets"
+                },
+            ),
+        ]);
+        Paragraph::new(Span::styled(hint, theme.placeholder())).render(chunks[1], buf);
+
+        let secret = state.editing_secret() && !state.revealed;
+        if let Some(editor) = &mut state.editor {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .style(theme.block(true))
+                .border_style(theme.border(true));
+            let inner = block.inner(chunks[2]);
+            editor.set_block(block);
+            editor.set_cursor_style(theme.cursor());
+            Widget::render(editor.widget(), chunks[2], buf);
+
+            // The spaces between words are hidden as well
+            let end = (inner.left()..inner.right())
+                .rev()
+                .find(|x| buf.get(*x, inner.top()).symbol != " ");
+            if let (true, Some(end)) = (secret, end) {
+                for x in inner.left()..=end {
+                    buf.get_mut(x, inner.top()).set_symbol("•");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    app::{Action, InputMode},
+    clipboard,
+    component::Component,
+    cookies::{Cookie, CookieJar},
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum CookieView {
+    /// Cookies set by the last response
+    #[default]
+    Response,
+    /// Cookies stored in the jar of the collection
+    Jar,
+}
+
+pub struct CookiesState {
+    pub received: Vec<Cookie>,
+    jar: Arc<Mutex<CookieJar>>,
+    view: CookieView,
+    state: TableState,
+    /// Input for the value of the selected cookie in the jar
+    editor: Option<TextArea<'static>>,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+    active: bool,
+}
+
+impl Component for CookiesState {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        if let Some(editor) = &mut self.editor {
+            match keys::input_action(key_event, binding) {
+                NormalKeyAction::Exit => self.editor = None,
+                NormalKeyAction::Accept => {
+                    let value = editor.clone().into_lines().join("");
+                    self.editor = None;
+                    return self.edit_jar(|jar, i| jar.cookies[i].value = value);
+                }
+                _ => {
+                    editor.input(key_event);
+                }
+            }
+            return None;
+        }
+
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::SwitchView => {
+                self.view = match self.view {
+                    CookieView::Response => CookieView::Jar,
+                    CookieView::Jar => CookieView::Response,
+                };
+                self.state.select(None);
+                None
+            }
+            NormalKeyAction::MoveUp => {
+                self.select(-1);
+                None
+            }
+            NormalKeyAction::MoveDown => {
+                self.select(1);
+                None
+            }
+            NormalKeyAction::Copy => {
+                let selected = self.state.selected();
+                let cookie = selected.and_then(|i| self.cookies().get(i).cloned())?;
+                clipboard::copy(format!("{}={}", cookie.name, cookie.value))
+                    .err()
+                    .map(Action::Error)
+            }
+            NormalKeyAction::Toggle if self.view == CookieView::Jar => match self.jar.lock() {
+                Ok(mut jar) => {
+                    jar.enabled = !jar.enabled;
+                    Some(Action::SaveCollection)
+                }
+                Err(_) => None,
+            },
+            NormalKeyAction::InsertMode | NormalKeyAction::Accept
+                if self.view == CookieView::Jar =>
+            {
+                let selected = self.state.selected();
+                if let Some(cookie) = selected.and_then(|i| self.cookies().get(i).cloned()) {
+                    let mut editor = TextArea::from([cookie.value]);
+                    editor.move_cursor(CursorMove::End);
+                    self.editor = Some(editor);
+                }
+                None
+            }
+            NormalKeyAction::Delete if self.view == CookieView::Jar => {
+                let action = self.edit_jar(|jar, i| {
+                    jar.cookies.remove(i);
+                });
+                self.select(0);
+                action
+            }
+            NormalKeyAction::Clear if self.view == CookieView::Jar => match self.jar.lock() {
+                Ok(mut jar) => {
+                    jar.clear();
+                    self.state.select(None);
+                    Some(Action::SaveCollection)
+                }
+                Err(_) => None,
+            },
+            key => key.relative_or_none(),
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        if self.editor.is_some() {
+            InputMode::Table
+        } else {
+            InputMode::Normal
+        }
+    }
+}
+
+impl CookiesState {
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap, jar: Arc<Mutex<CookieJar>>) -> Self {
+        Self {
+            received: Vec::new(),
+            jar,
+            view: CookieView::default(),
+            state: TableState::default(),
+            editor: None,
+            theme,
+            keymap,
+            active: false,
+        }
+    }
+
+    pub fn set_received(&mut self, cookies: Vec<Cookie>) {
+        self.received = cookies;
+        if self.view == CookieView::Response {
+            self.state.select(None);
+        }
+    }
+
+    /// Cookies of the current view
+    fn cookies(&self) -> Vec<Cookie> {
+        match self.view {
+            CookieView::Response => self.received.clone(),
+            CookieView::Jar => self
+                .jar
+                .lock()
+                .map(|jar| jar.cookies.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Applies `f` to the jar with the index of the selected cookie
+    fn edit_jar(&mut self, f: impl FnOnce(&mut CookieJar, usize)) -> Option<Action> {
+        let i = self.state.selected()?;
+        let mut jar = self.jar.lock().ok()?;
+        if i < jar.cookies.len() {
+            f(&mut jar, i);
+            Some(Action::SaveCollection)
+        } else {
+            None
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.cookies().len() as isize;
+        if len == 0 {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if offset == 0 => (i as isize).min(len - 1),
+            Some(i) => (i as isize + offset).rem_euclid(len),
+            None => 0,
+        };
+        self.state.select(Some(i as usize));
+    }
+}
+
+#[derive(Default)]
+pub struct Cookies;
+
+impl Cookies {
+    const WIDTHS: &'static [Constraint] = &[
+        Constraint::Percentage(15),
+        Constraint::Percentage(25),
+        Constraint::Percentage(15),
+        Constraint::Percentage(10),
+        Constraint::Percentage(20),
+        Constraint::Percentage(15),
+    ];
+}
+
+impl StatefulWidget for Cookies {
+    type State = CookiesState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                    Constraint::Length(if state.editor.is_some() { 3 } else { 0 }),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let theme = &state.theme;
+        let normal = Binding::Normal;
+        let title = match state.view {
+            CookieView::Response => format!(
+                "Response cookies ({})",
+                state
+                    .keymap
+                    .hint(&[(normal(NormalKeyAction::SwitchView), "show jar")])
+            ),
+            CookieView::Jar => {
+                let enabled = state.jar.lock().map(|jar| jar.enabled).unwrap_or(false);
+                format!(
+                    "Cookie jar {} ({})",
+                    if enabled { "enabled" } else { "disabled" },
+                    state.keymap.hint(&[
+                        (normal(NormalKeyAction::SwitchView), "response"),
+                        (normal(NormalKeyAction::Toggle), "toggle"),
+                        (normal(NormalKeyAction::InsertMode), "edit"),
+                        (normal(NormalKeyAction::Delete), "delete"),
+                        (normal(NormalKeyAction::Clear), "clear"),
+                    ])
+                )
+            }
+        };
+        Paragraph::new(Span::styled(title, theme.placeholder())).render(chunks[0], buf);
+
+        let rows = state.cookies().into_iter().map(|cookie| {
+            Row::new([
+                Cell::from(cookie.name.clone()),
+                Cell::from(cookie.value.clone()),
+                Cell::from(cookie.domain.clone()),
+                Cell::from(cookie.path.clone()),
+                Cell::from(cookie.expires_display()),
+                Cell::from(cookie.flags()),
+            ])
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(["Name", "Value", "Domain", "Path", "Expires", "Flags"])
+                    .style(theme.focused()),
+            )
+            .style(theme.block(state.active))
+            .widths(Self::WIDTHS)
+            .highlight_style(theme.selected());
+        StatefulWidget::render(table, chunks[1], buf, &mut state.state);
+
+        if let Some(editor) = &mut state.editor {
+            editor.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Value")
+                    .style(theme.block(true))
+                    .border_style(theme.border(true)),
+            );
+            editor.set_cursor_style(theme.cursor());
+            Widget::render(editor.widget(), chunks[2], buf);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crossterm::event::KeyEvent;
+use serde_json::Value;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::Span,
+    widgets::{Paragraph, StatefulWidget, Widget},
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    filter,
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+/// jq-like filter over a JSON response
+pub struct FilterState {
+    /// Input for the filter while it is edited
+    input: Option<TextArea<'static>>,
+    expression: String,
+    /// Why the filter could not be applied, the last result stays shown meanwhile
+    pub error: Option<String>,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+}
+
+impl FilterState {
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap) -> Self {
+        Self {
+            input: None,
+            expression: String::new(),
+            error: None,
+            theme,
+            keymap,
+        }
+    }
+
+    pub fn editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Whether the filter line is shown
+    pub fn visible(&self) -> bool {
+        self.editing() || !self.expression.is_empty()
+    }
+
+    pub fn open(&mut self) {
+        let mut input = TextArea::from([self.expression.as_str()]);
+        input.move_cursor(CursorMove::End);
+        input.set_cursor_line_style(Default::default());
+        self.input = Some(input);
+    }
+
+    /// Handles a key while the filter is edited, returns whether the expression changed
+    pub fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> bool {
+        let Some(input) = &mut self.input else {
+            return false;
+        };
+        match keys::input_action(key_event, binding) {
+            NormalKeyAction::Exit => {
+                self.input = None;
+                self.error = None;
+                !std::mem::take(&mut self.expression).is_empty()
+            }
+            NormalKeyAction::Accept => {
+                self.input = None;
+                false
+            }
+            _ => {
+                if !input.input(key_event) {
+                    return false;
+                }
+                self.expression = input.lines().join("");
+                true
+            }
+        }
+    }
+
+    /// Filters `value`, `None` when the filter failed and the error is set
+    pub fn apply(&mut self, value: &Value) -> Option<Value> {
+        match filter::apply(&self.expression, value) {
+            Ok(value) => {
+                self.error = None;
+                Some(value)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+/// Line with the filter above the content of the response
+#[derive(Default)]
+pub struct FilterLine;
+
+impl StatefulWidget for FilterLine {
+    type State = FilterState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let theme = &state.theme;
+        let error = match &state.error {
+            Some(error) => Span::styled(format!(" {error}"), theme.error()),
+            None if state.editing() => Span::styled(
+                format!(
+                    " {}",
+                    state
+                        .keymap
+                        .hint(&[(Binding::Normal(NormalKeyAction::Exit), "clear")])
+                ),
+                theme.placeholder(),
+            ),
+            None => Span::raw(""),
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Length(8),
+                    Constraint::Min(10),
+                    Constraint::Length(error.width() as u16),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        Paragraph::new(Span::styled(" Filter ", theme.focused())).render(chunks[0], buf);
+        match &mut state.input {
+            Some(input) => {
+                input.set_cursor_style(theme.cursor());
+                Widget::render(input.widget(), chunks[1], buf);
+            }
+            None => Paragraph::new(state.expression.as_str()).render(chunks[1], buf),
+        }
+        Paragraph::new(error).render(chunks[2], buf);
+    }
+}
@@ -0,0 +1,276 @@
+use serde_json::Value;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{List, ListItem, ListState, StatefulWidget},
+};
+
+use crate::{
+    app::Action,
+    clipboard,
+    highlight::TokenKind,
+    keys::{Binding, NormalKeyAction},
+    ui::theme::GlobalTheme,
+};
+
+/// Bodies with more nodes than this only have their first levels expanded initially
+const EXPAND_ALL_LIMIT: usize = 500;
+
+/// A value in the JSON document, nodes are stored in pre-order
+struct Node {
+    /// Object key or array index, `None` for the root
+    key: Option<String>,
+    pointer: String,
+    depth: usize,
+    parent: Option<usize>,
+    /// Index after the last descendant of this node
+    end: usize,
+    /// Whether the node is an object or an array
+    container: bool,
+    /// Number of direct children of objects and arrays
+    children: usize,
+    /// Value of scalars, `{}` or `[]` for objects and arrays
+    value: String,
+}
+
+/// Collapsible tree view of a JSON response
+pub struct JsonTreeState {
+    root: Value,
+    nodes: Vec<Node>,
+    collapsed: Vec<bool>,
+    /// Nodes that are not inside a collapsed node
+    visible: Vec<usize>,
+    /// Index in `visible` of the focused node
+    selected: usize,
+    offset: usize,
+    theme: GlobalTheme,
+}
+
+impl JsonTreeState {
+    pub fn new(root: Value, theme: GlobalTheme) -> Self {
+        let mut nodes = vec![];
+        flatten(&root, None, String::new(), None, 0, &mut nodes);
+        let collapsed = nodes
+            .iter()
+            .map(|node| nodes.len() > EXPAND_ALL_LIMIT && node.depth >= 2 && node.container)
+            .collect();
+        let mut state = Self {
+            root,
+            nodes,
+            collapsed,
+            visible: vec![],
+            selected: 0,
+            offset: 0,
+            theme,
+        };
+        state.update_visible();
+        state
+    }
+
+    pub fn handle_key(&mut self, binding: Option<Binding>) -> Option<Action> {
+        match NormalKeyAction::from(binding) {
+            NormalKeyAction::MoveUp => self.selected = self.selected.saturating_sub(1),
+            NormalKeyAction::MoveDown => {
+                self.selected = (self.selected + 1).min(self.visible.len() - 1)
+            }
+            NormalKeyAction::Accept => self.toggle(),
+            NormalKeyAction::Parent => {
+                if let Some(parent) = self.nodes[self.focused()].parent {
+                    self.focus(parent);
+                }
+            }
+            NormalKeyAction::NextSibling => {
+                let node = &self.nodes[self.focused()];
+                if let Some(parent) = node.parent {
+                    if node.end < self.nodes[parent].end {
+                        self.focus(node.end);
+                    }
+                }
+            }
+            NormalKeyAction::PrevSibling => {
+                let focused = self.focused();
+                if let Some(parent) = self.nodes[focused].parent {
+                    let mut child = parent + 1;
+                    while self.nodes[child].end < focused {
+                        child = self.nodes[child].end;
+                    }
+                    if child != focused {
+                        self.focus(child);
+                    }
+                }
+            }
+            NormalKeyAction::Copy => {
+                let text = match self.root.pointer(&self.nodes[self.focused()].pointer) {
+                    Some(Value::String(value)) => value.clone(),
+                    Some(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+                    None => return None,
+                };
+                return clipboard::copy(text).err().map(Action::Error);
+            }
+            NormalKeyAction::CopyPath => {
+                let pointer = self.nodes[self.focused()].pointer.clone();
+                return clipboard::copy(pointer).err().map(Action::Error);
+            }
+            key => return key.relative_or_none(),
+        }
+        None
+    }
+
+    /// Moves the focus by `rows`, used by the mouse wheel
+    pub fn scroll_by(&mut self, rows: i16) {
+        let last = self.visible.len() as isize - 1;
+        self.selected = (self.selected as isize + rows as isize).clamp(0, last) as usize;
+    }
+
+    fn focused(&self) -> usize {
+        self.visible[self.selected]
+    }
+
+    /// Focuses a node, it has to be visible
+    fn focus(&mut self, node: usize) {
+        if let Ok(i) = self.visible.binary_search(&node) {
+            self.selected = i;
+        }
+    }
+
+    /// Expands or collapses the focused node
+    fn toggle(&mut self) {
+        let focused = self.focused();
+        if self.nodes[focused].children > 0 {
+            self.collapsed[focused] = !self.collapsed[focused];
+            self.update_visible();
+            self.focus(focused);
+        }
+    }
+
+    fn update_visible(&mut self) {
+        self.visible.clear();
+        let mut i = 0;
+        while i < self.nodes.len() {
+            self.visible.push(i);
+            i = if self.collapsed[i] {
+                self.nodes[i].end
+            } else {
+                i + 1
+            };
+        }
+    }
+}
+
+fn flatten(
+    value: &Value,
+    key: Option<String>,
+    pointer: String,
+    parent: Option<usize>,
+    depth: usize,
+    nodes: &mut Vec<Node>,
+) {
+    let index = nodes.len();
+    let (summary, children): (&str, Vec<(String, &Value)>) = match value {
+        Value::Object(map) => ("{}", map.iter().map(|(k, v)| (k.clone(), v)).collect()),
+        Value::Array(items) => (
+            "[]",
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect(),
+        ),
+        scalar => {
+            nodes.push(Node {
+                key,
+                pointer,
+                depth,
+                parent,
+                end: index + 1,
+                container: false,
+                children: 0,
+                value: scalar.to_string(),
+            });
+            return;
+        }
+    };
+    nodes.push(Node {
+        key,
+        pointer: pointer.clone(),
+        depth,
+        parent,
+        end: index + 1,
+        container: true,
+        children: children.len(),
+        value: summary.to_string(),
+    });
+    for (key, child) in children {
+        let pointer = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+        flatten(child, Some(key), pointer, Some(index), depth + 1, nodes);
+    }
+    nodes[index].end = nodes.len();
+}
+
+#[derive(Default)]
+pub struct JsonTree;
+
+impl StatefulWidget for JsonTree {
+    type State = JsonTreeState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let height = area.height as usize;
+        if height == 0 {
+            return;
+        }
+        // Only the nodes that fit are turned into list items, bodies can be large
+        if state.selected < state.offset {
+            state.offset = state.selected;
+        } else if state.selected >= state.offset + height {
+            state.offset = state.selected + 1 - height;
+        }
+        let theme = &state.theme;
+        let items: Vec<ListItem> = state.visible[state.offset..]
+            .iter()
+            .take(height)
+            .map(|&i| {
+                let node = &state.nodes[i];
+                let mut line = vec![Span::raw("  ".repeat(node.depth))];
+                if node.container {
+                    line.push(Span::raw(if state.collapsed[i] { "▸ " } else { "▾ " }));
+                } else {
+                    line.push(Span::raw("  "));
+                }
+                if let Some(key) = &node.key {
+                    line.push(Span::styled(key.clone(), theme.syntax(TokenKind::Key)));
+                    line.push(Span::styled(": ", theme.syntax(TokenKind::Punctuation)));
+                }
+                let kind = match node.value.as_bytes().first() {
+                    _ if node.container => TokenKind::Punctuation,
+                    Some(b'"') => TokenKind::String,
+                    Some(b'-' | b'0'..=b'9') => TokenKind::Number,
+                    _ => TokenKind::Keyword,
+                };
+                line.push(Span::styled(node.value.clone(), theme.syntax(kind)));
+                if node.container {
+                    let unit = match (node.value.as_str(), node.children) {
+                        ("{}", 1) => "key",
+                        ("{}", _) => "keys",
+                        (_, 1) => "item",
+                        _ => "items",
+                    };
+                    line.push(Span::styled(
+                        format!(" {} {unit}", node.children),
+                        theme.placeholder(),
+                    ));
+                }
+                ListItem::new(Spans::from(line))
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(state.selected - state.offset));
+        StatefulWidget::render(
+            List::new(items).highlight_style(theme.selected()),
+            area,
+            buf,
+            &mut list_state,
+        );
+    }
+}
@@ -7,35 +7,85 @@ use crossterm::event::KeyEvent;
 use tui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    text::Spans,
+    style::Style,
+    text::{Span, Spans},
     widgets::{Block, Borders, Paragraph, StatefulWidget, Tabs, Widget},
 };
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, Input, Key, TextArea};
 
 use crate::{
     app::{Action, InputMode, Movement, PaneType},
+    body::RAW_CONTENT_TYPES,
+    client::validate_setting,
+    collection::{BodyMode, KeyValue, SavedRequest},
     component::Component,
-    keys::NormalKeyAction,
+    environment::{self, Environments},
+    extract::validate_extraction,
+    highlight::{Highlighter, Language},
+    http::validate_header,
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
     pane::Pane,
-    ui::theme::GlobalTheme,
+    query,
+    runner::validate_assertion,
+    ui::{
+        key_value_table::{KeyValueTable, KeyValueTableState},
+        mouse,
+        theme::GlobalTheme,
+    },
 };
 
-use super::RightStatePane;
+use super::{
+    auth::{AuthEditor, AuthState},
+    RightStatePane,
+};
 
 pub struct RequestState<'a> {
     tab_index: usize,
     active: bool,
     theme: GlobalTheme,
+    keymap: GlobalKeymap,
     pub input_line: TextArea<'a>,
+    body_mode: BodyMode,
     pub body: TextArea<'a>,
+    raw_content_type: String,
+    /// Fields of an url-encoded or multipart body
+    pub form: KeyValueTableState<'a>,
+    /// Path of the file of a binary body
+    pub file: TextArea<'a>,
+    body_highlighter: Highlighter,
+    /// First visible row and column of the body
+    body_scroll: (u16, u16),
+    /// Rows of the body that were last shown, a page when it is scrolled
+    body_height: u16,
+    pub headers: KeyValueTableState<'a>,
+    pub query: KeyValueTableState<'a>,
+    pub assertions: KeyValueTableState<'a>,
+    pub extractions: KeyValueTableState<'a>,
+    pub settings: KeyValueTableState<'a>,
+    pub auth: AuthState<'a>,
     input_mode: InputMode,
     selected_method: Arc<Mutex<reqwest::Method>>,
+    environments: Arc<Mutex<Environments>>,
+    /// Undefined variables of the editor, `None` after an edit
+    unresolved: Option<Unresolved>,
+    /// Where the request and its tabs were last rendered
+    pub area: Rect,
+    tabs_area: Rect,
+}
+
+/// Variables of the editor that are not defined in the environment they were checked against
+struct Unresolved {
+    environment: Option<String>,
+    variables: Vec<KeyValue>,
+    names: Vec<String>,
 }
 
 impl<'a> Component for RequestState<'a> {
-    fn handle_key(&mut self, key_event: KeyEvent) -> Option<Action> {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        // Any key may edit the request
+        self.unresolved = None;
         match &mut self.input_mode {
-            InputMode::Normal => match NormalKeyAction::from(key_event) {
+            InputMode::Normal => match NormalKeyAction::from(binding) {
                 NormalKeyAction::PrevTab => {
                     self.prev();
                     None
@@ -48,36 +98,105 @@ impl<'a> Component for RequestState<'a> {
                     self.input_mode = InputMode::Hostname;
                     None
                 }
+                NormalKeyAction::SwitchView if Request::OPTIONS[self.tab_index] == "Body" => {
+                    let i = Request::BODY_MODES
+                        .iter()
+                        .position(|(mode, _)| *mode == self.body_mode)
+                        .unwrap_or(0);
+                    self.body_mode = Request::BODY_MODES[(i + 1) % Request::BODY_MODES.len()].0;
+                    self.form.set_files(self.body_mode == BodyMode::Multipart);
+                    None
+                }
+                NormalKeyAction::Toggle
+                    if Request::OPTIONS[self.tab_index] == "Body"
+                        && self.body_mode == BodyMode::Raw =>
+                {
+                    let next = RAW_CONTENT_TYPES
+                        .iter()
+                        .position(|t| *t == self.raw_content_type)
+                        .map_or(0, |i| (i + 1) % RAW_CONTENT_TYPES.len());
+                    self.raw_content_type = RAW_CONTENT_TYPES[next].to_string();
+                    None
+                }
                 // TODO: Tabs should accept focus, think about how to solve this with the input line.
                 NormalKeyAction::Accept => {
-                    if Request::OPTIONS[self.tab_index] == "Body" {
+                    if Request::OPTIONS[self.tab_index] == "Body" && !self.form_body() {
                         self.input_mode = InputMode::Body;
+                    } else if Request::OPTIONS[self.tab_index] == "Auth" {
+                        self.auth.set_active(true);
+                        self.input_mode = InputMode::Auth;
+                    } else if let Some(table) = self.active_table() {
+                        table.set_active(true);
+                        self.input_mode = InputMode::Table;
                     }
                     None
                 }
                 key => key.relative_or_none(),
             },
 
-            InputMode::Hostname => match NormalKeyAction::from(key_event) {
+            InputMode::Hostname => match keys::input_action(key_event, binding) {
                 NormalKeyAction::Exit | NormalKeyAction::Accept => {
                     self.input_mode = InputMode::Normal;
                     None
                 }
                 _ => {
-                    self.input_line.input(key_event);
+                    if self.input_line.input(key_event) {
+                        self.sync_query_from_url();
+                    }
                     None
                 }
             },
-            InputMode::Body => match NormalKeyAction::from(key_event) {
+            InputMode::Body if self.body_mode == BodyMode::Binary => {
+                // The path is a single line, accept leaves it like the URL
+                match keys::input_action(key_event, binding) {
+                    NormalKeyAction::Exit | NormalKeyAction::Accept => {
+                        self.input_mode = InputMode::Normal
+                    }
+                    _ => {
+                        self.file.input(key_event);
+                    }
+                }
+                None
+            }
+            InputMode::Body => match keys::input_action(key_event, binding) {
                 NormalKeyAction::Exit => {
                     self.input_mode = InputMode::Normal;
                     None
                 }
                 _ => {
-                    self.body.input(key_event);
+                    if let Some(pages) = page_scroll(key_event) {
+                        self.scroll_by(pages * self.body_height as i16);
+                        return None;
+                    }
+                    let row = self.body.cursor().0;
+                    if self.body.input(key_event) {
+                        // Edits happen at the cursor, undo and redo move it to the edit
+                        self.body_highlighter
+                            .invalidate(row.min(self.body.cursor().0));
+                    }
                     None
                 }
             },
+            InputMode::Table => {
+                if let Some(table) = self.active_table() {
+                    table.handle_key(key_event, binding);
+                    if !table.active() {
+                        self.input_mode = InputMode::Normal;
+                    }
+                }
+                if self.query.take_changed() {
+                    self.sync_url_from_query();
+                }
+                None
+            }
+            InputMode::Auth => {
+                self.auth.handle_key(key_event, binding);
+                if !self.auth.active() {
+                    self.input_mode = InputMode::Normal;
+                }
+                None
+            }
+            InputMode::Name | InputMode::Search | InputMode::Filter => None,
         }
     }
 
@@ -113,15 +232,50 @@ impl<'a> Pane for RequestState<'a> {
 
 impl<'a> RequestState<'a> {
     const TAB_LEN: usize = Request::OPTIONS.len();
-    pub fn new(theme: GlobalTheme, selected_method: Arc<Mutex<reqwest::Method>>) -> Self {
+    pub fn new(
+        theme: GlobalTheme,
+        keymap: GlobalKeymap,
+        selected_method: Arc<Mutex<reqwest::Method>>,
+        environments: Arc<Mutex<Environments>>,
+    ) -> Self {
         Self {
             tab_index: 0,
-            theme,
+            theme: theme.clone(),
+            keymap: keymap.clone(),
             active: false,
             input_line: TextArea::default(),
+            body_mode: BodyMode::Json,
             body: TextArea::from("{\n\n}".lines()),
+            raw_content_type: RAW_CONTENT_TYPES[0].to_string(),
+            form: KeyValueTableState::new(theme.clone(), keymap.clone(), None),
+            file: TextArea::default(),
+            body_highlighter: Highlighter::default(),
+            body_scroll: (0, 0),
+            body_height: 0,
+            headers: KeyValueTableState::new(theme.clone(), keymap.clone(), Some(validate_header)),
+            query: KeyValueTableState::new(theme.clone(), keymap.clone(), None),
+            assertions: KeyValueTableState::new(
+                theme.clone(),
+                keymap.clone(),
+                Some(validate_assertion),
+            ),
+            extractions: KeyValueTableState::new(
+                theme.clone(),
+                keymap.clone(),
+                Some(validate_extraction),
+            ),
+            settings: KeyValueTableState::new(
+                theme.clone(),
+                keymap.clone(),
+                Some(validate_setting),
+            ),
+            auth: AuthState::new(theme.clone(), keymap),
             input_mode: InputMode::Normal,
             selected_method,
+            environments,
+            unresolved: None,
+            area: Rect::default(),
+            tabs_area: Rect::default(),
         }
     }
 
@@ -137,6 +291,270 @@ impl<'a> RequestState<'a> {
         assert!(index < Self::TAB_LEN);
         self.tab_index = index;
     }
+
+    /// Selects the tab at the position of a click
+    pub fn click(&mut self, column: u16, row: u16) {
+        if let Some(i) = mouse::tab_at(Request::OPTIONS, self.tabs_area, column, row) {
+            self.select(i);
+        }
+    }
+
+    /// Scrolls the body by `rows` when it is shown
+    pub fn scroll_by(&mut self, rows: i16) {
+        if Request::OPTIONS[self.tab_index] == "Body"
+            && matches!(self.body_mode, BodyMode::Json | BodyMode::Raw)
+        {
+            let last = self.body.lines().len().saturating_sub(1) as i32;
+            let top = (self.body_scroll.0 as i32 + rows as i32).clamp(0, last) as u16;
+            self.body_scroll.0 = top;
+            // The cursor stays visible, like it does when a text area is scrolled
+            let (row, column) = self.body.cursor();
+            let row = (row as u16).clamp(top, top.saturating_add(self.body_height.max(1) - 1));
+            self.body.move_cursor(CursorMove::Jump(row, column as u16));
+        }
+    }
+
+    pub fn selected_method(&self) -> &Arc<Mutex<reqwest::Method>> {
+        &self.selected_method
+    }
+
+    /// Replaces the editor contents with a saved request
+    pub fn load(&mut self, request: &SavedRequest) {
+        self.input_line = TextArea::from([request.url.as_str()]);
+        self.input_line.move_cursor(CursorMove::End);
+        self.body_mode = request.body_mode;
+        self.body = TextArea::from(request.body.lines());
+        self.body_scroll = (0, 0);
+        self.body_highlighter.invalidate(0);
+        self.raw_content_type = request.raw_content_type.clone();
+        self.form.set_rows(request.form.clone());
+        self.form.set_files(self.body_mode == BodyMode::Multipart);
+        self.file = TextArea::from([request.file.as_str()]);
+        self.file.move_cursor(CursorMove::End);
+        self.headers.set_rows(request.headers.clone());
+        self.query
+            .set_rows(query::merge(&request.url, &request.query));
+        self.auth.set_auth(request.auth.clone());
+        self.assertions.set_rows(request.assertions.clone());
+        self.extractions.set_rows(request.extractions.clone());
+        self.settings.set_rows(request.settings.clone());
+        self.unresolved = None;
+    }
+
+    /// Writes the editor contents back into a saved request
+    pub fn store(&self, request: &mut SavedRequest) {
+        request.url = self.url();
+        request.body_mode = self.body_mode;
+        request.body = self.body.lines().join("\n");
+        request.raw_content_type = self.raw_content_type.clone();
+        request.form = self.form.rows.clone();
+        request.file = self.file.lines().join("");
+        request.headers = self.headers.rows.clone();
+        request.query = self.query.rows.clone();
+        request.auth = self.auth.auth().clone();
+        request.assertions = self.assertions.rows.clone();
+        request.extractions = self.extractions.rows.clone();
+        request.settings = self.settings.rows.clone();
+    }
+
+    pub fn url(&self) -> String {
+        self.input_line.clone().into_lines().join("")
+    }
+
+    /// Updates the query table after the url was edited
+    fn sync_query_from_url(&mut self) {
+        let params = query::merge(&self.url(), &self.query.rows);
+        self.query.set_rows(params);
+    }
+
+    /// Rewrites the query of the url after the query table was edited
+    fn sync_url_from_query(&mut self) {
+        let url = query::with_query(&self.url(), &self.query.rows);
+        self.input_line = TextArea::from([url]);
+        self.input_line.move_cursor(CursorMove::End);
+    }
+
+    /// Highlights the placeholders of variables that are not defined in the active
+    /// environment, returns the name of that environment and the undefined variables.
+    /// They are only looked up again after an edit or when the environment changed.
+    fn highlight_unresolved(&mut self) -> (Option<String>, Vec<String>) {
+        let (name, variables) = match self.environments.lock() {
+            Ok(environments) => (environments.active.clone(), environments.variables()),
+            Err(_) => (None, vec![]),
+        };
+        let checked = self
+            .unresolved
+            .as_ref()
+            .filter(|u| u.environment == name && u.variables == variables);
+        let unresolved = match checked {
+            Some(unresolved) => unresolved.names.clone(),
+            None => {
+                let mut request = SavedRequest::default();
+                self.store(&mut request);
+                let unresolved = environment::unresolved(&request, &variables);
+                let pattern = if unresolved.is_empty() {
+                    String::new()
+                } else {
+                    let names: Vec<_> = unresolved.iter().map(|n| regex::escape(n)).collect();
+                    format!(r"\{{\{{\s*(?:{})\s*\}}\}}", names.join("|"))
+                };
+                for text in [&mut self.input_line, &mut self.body, &mut self.file] {
+                    // The names are escaped, so the pattern is always valid
+                    let _ = text.set_search_pattern(&pattern);
+                }
+                self.unresolved = Some(Unresolved {
+                    environment: name.clone(),
+                    variables,
+                    names: unresolved.clone(),
+                });
+                unresolved
+            }
+        };
+        for text in [&mut self.input_line, &mut self.body, &mut self.file] {
+            text.set_search_style(self.theme.error());
+        }
+        (name, unresolved)
+    }
+
+    /// Renders the visible lines of the body with their syntax colours. The text area only
+    /// edits the body, its scroll position is private so the body is scrolled here.
+    fn render_body_text(&mut self, area: Rect, buf: &mut Buffer) {
+        let cursor = self.body.cursor();
+        let (top, left) = self.body_scroll;
+        self.body_scroll = (
+            scroll_to(top, cursor.0, area.height),
+            scroll_to(left, cursor.1, area.width),
+        );
+        self.body_height = area.height;
+        let content_type = self
+            .headers
+            .rows
+            .iter()
+            .rev()
+            .find(|row| row.enabled && row.key.eq_ignore_ascii_case("content-type"))
+            .map(|row| row.value.as_str())
+            .or((self.body_mode == BodyMode::Raw).then_some(self.raw_content_type.as_str()));
+        let first_line = self
+            .body
+            .lines()
+            .iter()
+            .find(|line| !line.trim().is_empty());
+        self.body_highlighter.set_language(Language::detect(
+            content_type,
+            first_line.map_or("", String::as_str),
+        ));
+
+        let tab = " ".repeat(self.body.tab_length() as usize);
+        let lines = self.body.lines();
+        let mut text = vec![];
+        for row in (self.body_scroll.0 as usize..lines.len()).take(area.height as usize) {
+            let line = &lines[row];
+            let tokens = self.body_highlighter.line(lines, row);
+            // Undefined variables stay highlighted on top of the syntax
+            let unresolved: Vec<_> = self
+                .body
+                .search_pattern()
+                .map(|pattern| pattern.find_iter(line).map(|m| m.range()).collect())
+                .unwrap_or_default();
+            let base = if row == cursor.0 {
+                self.body.cursor_line_style()
+            } else {
+                Style::default()
+            };
+            let mut spans: Vec<Span> = vec![];
+            let mut push = |text: &str, style: Style| match spans.last_mut() {
+                Some(span) if span.style == style => span.content.to_mut().push_str(text),
+                _ => spans.push(Span::styled(text.to_string(), style)),
+            };
+            for (i, (byte, c)) in line.char_indices().enumerate() {
+                // Tokens and matches are sorted and do not overlap
+                let style = match unresolved.iter().find(|range| range.contains(&byte)) {
+                    Some(_) => self.body.search_style(),
+                    None => {
+                        let t = tokens.partition_point(|token| token.range.end <= byte);
+                        tokens
+                            .get(t)
+                            .filter(|token| token.range.contains(&byte))
+                            .map_or(Style::default(), |token| self.theme.syntax(token.kind))
+                    }
+                };
+                let style = if (row, i) == cursor {
+                    self.body.cursor_style()
+                } else {
+                    base.patch(style)
+                };
+                let mut char = [0; 4];
+                push(
+                    match c {
+                        '\t' => &tab,
+                        c => c.encode_utf8(&mut char),
+                    },
+                    style,
+                );
+            }
+            if row == cursor.0 && cursor.1 >= line.chars().count() {
+                push(" ", self.body.cursor_style());
+            }
+            text.push(Spans::from(spans));
+        }
+        Paragraph::new(text)
+            .style(self.body.style())
+            .scroll((0, self.body_scroll.1))
+            .render(area, buf);
+    }
+
+    /// Whether the body is made of the form rows instead of the text
+    fn form_body(&self) -> bool {
+        matches!(self.body_mode, BodyMode::UrlEncoded | BodyMode::Multipart)
+    }
+
+    /// The key/value table shown in the current tab
+    fn active_table(&mut self) -> Option<&mut KeyValueTableState<'a>> {
+        match Request::OPTIONS[self.tab_index] {
+            "Body" if self.form_body() => Some(&mut self.form),
+            "Headers" => Some(&mut self.headers),
+            "Query" => Some(&mut self.query),
+            "Tests" => Some(&mut self.assertions),
+            "Extract" => Some(&mut self.extractions),
+            "Settings" => Some(&mut self.settings),
+            _ => None,
+        }
+    }
+}
+
+/// The first visible row or column that keeps `cursor` in view, like a text area scrolls
+fn scroll_to(top: u16, cursor: usize, length: u16) -> u16 {
+    let cursor = cursor.min(u16::MAX as usize) as u16;
+    if cursor < top {
+        cursor
+    } else if top.saturating_add(length) <= cursor {
+        cursor + 1 - length
+    } else {
+        top
+    }
+}
+
+/// `1` or `-1` for the keys that scroll a text area by a page
+fn page_scroll(key_event: KeyEvent) -> Option<i16> {
+    match Input::from(key_event) {
+        Input {
+            key: Key::PageDown, ..
+        }
+        | Input {
+            key: Key::Char('v'),
+            ctrl: true,
+            alt: false,
+        } => Some(1),
+        Input {
+            key: Key::PageUp, ..
+        }
+        | Input {
+            key: Key::Char('v'),
+            ctrl: false,
+            alt: true,
+        } => Some(-1),
+        _ => None,
+    }
 }
 
 #[derive(Clone, Default)]
@@ -145,18 +563,44 @@ pub struct Request<'a> {
 }
 
 impl<'a> Request<'a> {
-    const OPTIONS: &'static [&'static str] = &["Query", "Headers", "Auth", "Body"];
+    const OPTIONS: &'static [&'static str] = &[
+        "Query", "Headers", "Auth", "Body", "Tests", "Extract", "Settings",
+    ];
+
+    const BODY_MODES: &'static [(BodyMode, &'static str)] = &[
+        (BodyMode::Json, "JSON"),
+        (BodyMode::Raw, "Raw"),
+        (BodyMode::UrlEncoded, "Form url-encoded"),
+        (BodyMode::Multipart, "Multipart form"),
+        (BodyMode::Binary, "Binary file"),
+    ];
 }
 
 impl<'a> StatefulWidget for Request<'a> {
     type State = RequestState<'a>;
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let (environment, unresolved) = state.highlight_unresolved();
+        let mut title = vec![Span::raw("Request")];
+        if let Some(environment) = environment {
+            title.push(Span::styled(
+                format!(" [{environment}]"),
+                state.theme.focused(),
+            ));
+        }
+        if !unresolved.is_empty() {
+            title.push(Span::styled(
+                format!(" Unresolved: {}", unresolved.join(", ")),
+                state.theme.error(),
+            ));
+        }
         let block = Block::default()
-            .title("Request")
+            .title(Spans::from(title))
             .borders(Borders::ALL)
-            .style(state.theme.block(state.active));
+            .style(state.theme.block(state.active))
+            .border_style(state.theme.border(state.active));
         let request_area = block.inner(area);
         block.render(area, buf);
+        state.area = area;
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -175,12 +619,17 @@ impl<'a> StatefulWidget for Request<'a> {
         let tabs = Tabs::new(titles)
             .block(Block::default().borders(Borders::ALL))
             .select(state.tab_index)
-            .highlight_style(state.theme.selected());
+            .highlight_style(state.theme.tab_selected());
 
+        let editing = matches!(
+            state.input_mode,
+            InputMode::Body | InputMode::Table | InputMode::Auth
+        );
         let inner = Block::default()
             .title(Self::OPTIONS[state.tab_index])
             .borders(Borders::ALL)
-            .style(state.theme.block(state.input_mode == InputMode::Body));
+            .style(state.theme.block(editing))
+            .border_style(state.theme.border(editing));
 
         let bar_chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -195,7 +644,8 @@ impl<'a> StatefulWidget for Request<'a> {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .style(state.theme.block(false)),
+                    .style(state.theme.block(false))
+                    .border_style(state.theme.border(false)),
             )
             .style(state.theme.focused())
             .alignment(Alignment::Center);
@@ -204,18 +654,134 @@ impl<'a> StatefulWidget for Request<'a> {
 
         let hostname_block = Block::default()
             .borders(Borders::ALL)
-            .style(state.theme.block(state.input_mode == InputMode::Hostname));
+            .style(state.theme.block(state.input_mode == InputMode::Hostname))
+            .border_style(state.theme.border(state.input_mode == InputMode::Hostname));
         let area = bar_chunks[1];
         let inner_host_area = hostname_block.inner(area);
         hostname_block.render(area, buf);
-        Widget::render(state.input_line.widget(), inner_host_area, buf);
+        if state.input_line.is_empty() && state.input_mode != InputMode::Hostname {
+            Paragraph::new("http://localhost:8080")
+                .style(state.theme.placeholder())
+                .render(inner_host_area, buf);
+        } else {
+            state.input_line.set_style(state.theme.hostname());
+            state.input_line.set_cursor_style(state.theme.cursor());
+            Widget::render(state.input_line.widget(), inner_host_area, buf);
+        }
 
-        if Request::OPTIONS[state.tab_index] == "Body" {
-            let area = chunks[2];
-            Widget::render(state.body.widget(), inner.inner(area), buf);
+        // The block is rendered first, its style would hide the highlighting of the body
+        let content_area = inner.inner(chunks[2]);
+        inner.render(chunks[2], buf);
+        match Request::OPTIONS[state.tab_index] {
+            "Body" => render_body(content_area, buf, state),
+            "Headers" => StatefulWidget::render(
+                KeyValueTable::default(),
+                content_area,
+                buf,
+                &mut state.headers,
+            ),
+            "Query" => StatefulWidget::render(
+                KeyValueTable::default(),
+                content_area,
+                buf,
+                &mut state.query,
+            ),
+            "Tests" => StatefulWidget::render(
+                KeyValueTable::default(),
+                content_area,
+                buf,
+                &mut state.assertions,
+            ),
+            "Extract" => StatefulWidget::render(
+                KeyValueTable::default(),
+                content_area,
+                buf,
+                &mut state.extractions,
+            ),
+            "Settings" => StatefulWidget::render(
+                KeyValueTable::default(),
+                content_area,
+                buf,
+                &mut state.settings,
+            ),
+            "Auth" => {
+                StatefulWidget::render(AuthEditor::default(), content_area, buf, &mut state.auth)
+            }
+            _ => (),
         }
 
+        state.tabs_area = chunks[1];
         tabs.render(chunks[1], buf);
-        inner.render(chunks[2], buf);
+    }
+}
+
+/// Renders the mode of the body above the editor of that mode
+fn render_body(area: Rect, buf: &mut Buffer, state: &mut RequestState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(area);
+    let label = Request::BODY_MODES
+        .iter()
+        .find(|(mode, _)| *mode == state.body_mode)
+        .map_or("", |(_, label)| label);
+    let mut line = vec![
+        Span::raw("Mode: "),
+        Span::styled(label, state.theme.focused()),
+    ];
+    if state.body_mode == BodyMode::Raw {
+        line.push(Span::raw(" Content type: "));
+        line.push(Span::styled(
+            state.raw_content_type.as_str(),
+            state.theme.focused(),
+        ));
+    }
+    let switch_view = Binding::Normal(NormalKeyAction::SwitchView);
+    let hint = match state.body_mode {
+        BodyMode::Raw => state.keymap.hint(&[
+            (switch_view, "mode"),
+            (Binding::Normal(NormalKeyAction::Toggle), "content type"),
+        ]),
+        BodyMode::Multipart => state.keymap.hint(&[(switch_view, "mode, text/file")]),
+        _ => state.keymap.hint(&[(switch_view, "mode")]),
+    };
+    line.push(Span::styled(format!("  {hint}"), state.theme.placeholder()));
+    Paragraph::new(Spans::from(line)).render(chunks[0], buf);
+
+    let area = chunks[1];
+    match state.body_mode {
+        BodyMode::Json | BodyMode::Raw => {
+            state.render_body_text(area, buf);
+        }
+        BodyMode::UrlEncoded | BodyMode::Multipart => {
+            StatefulWidget::render(KeyValueTable::default(), area, buf, &mut state.form)
+        }
+        BodyMode::Binary => {
+            let editing = state.input_mode == InputMode::Body;
+            let block = Block::default()
+                .title("File")
+                .borders(Borders::ALL)
+                .style(state.theme.block(editing))
+                .border_style(state.theme.border(editing));
+            let inner = block.inner(Rect {
+                height: area.height.min(3),
+                ..area
+            });
+            block.render(
+                Rect {
+                    height: area.height.min(3),
+                    ..area
+                },
+                buf,
+            );
+            if state.file.is_empty() && !editing {
+                Paragraph::new("Path of the file to send")
+                    .style(state.theme.placeholder())
+                    .render(inner, buf);
+            } else {
+                state.file.set_cursor_style(state.theme.cursor());
+                Widget::render(state.file.widget(), inner, buf);
+            }
+        }
     }
 }
@@ -1,38 +1,132 @@
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use cli_clipboard::{ClipboardContext, ClipboardProvider};
 use crossterm::event::KeyEvent;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use tokio::time::Instant;
 use tui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    text::{Span, Spans},
-    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, Tabs, Widget, Wrap},
+    text::{Span, Spans, Text},
+    widgets::{
+        Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Tabs, Widget, Wrap,
+    },
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    app::{Action, Movement, PaneType},
+    app::{Action, InputMode, Movement, PaneType},
+    clipboard,
     component::Component,
-    keys::NormalKeyAction,
+    cookies::CookieJar,
+    highlight::{highlight, Language, Token},
+    http::Redirect,
+    keys::{Binding, GlobalKeyAction, GlobalKeymap, NormalKeyAction},
     pane::Pane,
-    ui::theme::GlobalTheme,
+    timing::Timing,
+    ui::{mouse, theme::GlobalTheme},
+};
+
+use super::{
+    cookies::{Cookies, CookiesState},
+    filter::{FilterLine, FilterState},
+    json_tree::{JsonTree, JsonTreeState},
+    search::{SearchPrompt, SearchState},
+    RightStatePane,
 };
 
-use super::RightStatePane;
+/// Order in which the response headers are listed
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum HeaderOrder {
+    #[default]
+    Received,
+    Ascending,
+    Descending,
+}
+
+impl HeaderOrder {
+    fn next(self) -> Self {
+        match self {
+            Self::Received => Self::Ascending,
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Received,
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
 pub struct ResponseState {
     tab_index: usize,
     pub status_code: reqwest::StatusCode,
     theme: GlobalTheme,
+    keymap: GlobalKeymap,
     active: bool,
-    pub response: String,
+    response: String,
+    /// The response with syntax highlighting
+    text: Text<'static>,
+    /// Syntax tokens of every line of the response
+    tokens: Vec<Vec<Token>>,
+    search: SearchState,
+    /// Scroll to the current match on the next render
+    scroll_to_match: bool,
+    /// The parsed response when it is JSON, before it is filtered
+    value: Option<serde_json::Value>,
+    filter: FilterState,
+    /// Tree view of the response when it is JSON
+    json: Option<JsonTreeState>,
+    /// Show the response text instead of the tree view
+    raw: bool,
+    pub headers: HeaderMap,
     pub time: Duration,
+    /// Whether the response was received over a connection of an earlier response, unknown
+    /// for the history
+    pub reused: Option<bool>,
+    /// Redirects that were followed to get the response
+    pub redirects: Vec<Redirect>,
+    /// How often the request was sent again
+    pub retries: u32,
+    pub timing: Option<Timing>,
+    /// When the request that is being sent was started
+    pub sending: Option<Instant>,
     scroll: u16,
+    /// Where the response, its tabs and its content were last rendered
+    pub area: Rect,
+    tabs_area: Rect,
+    content_area: Rect,
+    header_order: HeaderOrder,
+    header_state: TableState,
+    pub cookies: CookiesState,
+    /// Variables that were set from the last response
+    pub extracted: Vec<String>,
+    /// Variables that could not be extracted, with the reason
+    pub extract_errors: Vec<String>,
+    /// An error of an action like copying, shown in the status line
+    pub error: Option<String>,
 }
 
 impl Component for ResponseState {
-    fn handle_key(&mut self, key_event: KeyEvent) -> Option<Action> {
-        match NormalKeyAction::from(key_event) {
+    fn handle_key(&mut self, key_event: KeyEvent, binding: Option<Binding>) -> Option<Action> {
+        let cookies_tab = Response::OPTIONS[self.tab_index] == "Cookies";
+        let content_tab = Response::OPTIONS[self.tab_index] == "Content";
+        if cookies_tab && self.cookies.input_mode() != InputMode::Normal {
+            return self.cookies.handle_key(key_event, binding);
+        }
+        if self.filter.editing() {
+            if self.filter.handle_key(key_event, binding) {
+                self.apply_filter();
+            }
+            return None;
+        }
+        if self.search.prompt_open() {
+            if self.search.handle_key(key_event, binding, &self.response) {
+                self.restyle();
+                self.scroll_to_match = true;
+            }
+            return None;
+        }
+
+        match NormalKeyAction::from(binding) {
             NormalKeyAction::PrevTab => {
                 self.prev();
                 None
@@ -41,6 +135,60 @@ impl Component for ResponseState {
                 self.next();
                 None
             }
+            _ if cookies_tab => self.cookies.handle_key(key_event, binding),
+            NormalKeyAction::Filter if content_tab && self.value.is_some() => {
+                self.filter.open();
+                None
+            }
+            NormalKeyAction::Search if content_tab => {
+                // Matches are shown in the text, so the tree switches to the raw view
+                self.raw = true;
+                self.search.open();
+                None
+            }
+            NormalKeyAction::New if content_tab && !self.search.matches.is_empty() => {
+                self.select_match(1);
+                None
+            }
+            NormalKeyAction::PrevMatch if content_tab && !self.search.matches.is_empty() => {
+                self.select_match(-1);
+                None
+            }
+            NormalKeyAction::Exit if content_tab && self.search.active() => {
+                self.search.clear();
+                self.restyle();
+                None
+            }
+            NormalKeyAction::SwitchView if content_tab && self.json.is_some() => {
+                self.raw = !self.raw;
+                None
+            }
+            _ if content_tab && !self.raw && self.json.is_some() => {
+                self.json.as_mut()?.handle_key(binding)
+            }
+            NormalKeyAction::MoveUp if Response::OPTIONS[self.tab_index] == "Headers" => {
+                self.select_header(-1);
+                None
+            }
+            NormalKeyAction::MoveDown if Response::OPTIONS[self.tab_index] == "Headers" => {
+                self.select_header(1);
+                None
+            }
+            NormalKeyAction::Sort if Response::OPTIONS[self.tab_index] == "Headers" => {
+                self.header_order = self.header_order.next();
+                None
+            }
+            NormalKeyAction::Copy if Response::OPTIONS[self.tab_index] == "Headers" => {
+                let selected = self.header_state.selected();
+                if let Some((name, value)) =
+                    selected.and_then(|i| self.header_rows().get(i).cloned())
+                {
+                    return clipboard::copy(format!("{name}: {value}"))
+                        .err()
+                        .map(Action::Error);
+                }
+                None
+            }
             NormalKeyAction::MoveUp => {
                 self.scroll += 1;
                 None
@@ -51,11 +199,9 @@ impl Component for ResponseState {
                 }
                 None
             }
-            NormalKeyAction::Copy => {
-                let mut ctx = ClipboardContext::new().unwrap();
-                ctx.set_contents(self.response.clone()).unwrap();
-                None
-            }
+            NormalKeyAction::Copy => clipboard::copy(self.response.clone())
+                .err()
+                .map(Action::Error),
             key => key.relative_or_none(),
         }
     }
@@ -65,7 +211,19 @@ impl Component for ResponseState {
     }
 
     fn set_active(&mut self, active: bool) {
-        self.active = active
+        self.active = active;
+        self.cookies.set_active(active);
+    }
+
+    #[inline(always)]
+    fn input_mode(&self) -> InputMode {
+        if self.filter.editing() {
+            return InputMode::Filter;
+        }
+        if self.search.prompt_open() {
+            return InputMode::Search;
+        }
+        self.cookies.input_mode()
     }
 }
 
@@ -89,18 +247,184 @@ impl Pane for ResponseState {
 impl ResponseState {
     const TAB_LEN: usize = Response::OPTIONS.len();
 
-    pub fn new(theme: GlobalTheme) -> Self {
+    pub fn new(
+        theme: GlobalTheme,
+        keymap: GlobalKeymap,
+        cookie_jar: Arc<Mutex<CookieJar>>,
+    ) -> Self {
         Self {
+            cookies: CookiesState::new(theme.clone(), keymap.clone(), cookie_jar),
+            search: SearchState::new(theme.clone(), keymap.clone()),
+            filter: FilterState::new(theme.clone(), keymap.clone()),
             tab_index: 0,
             status_code: reqwest::StatusCode::default(),
             theme,
+            keymap,
             active: false,
             response: String::default(),
+            text: Text::default(),
+            tokens: vec![],
+            scroll_to_match: false,
+            value: None,
+            json: None,
+            raw: false,
+            headers: HeaderMap::default(),
             time: Duration::default(),
+            reused: None,
+            redirects: vec![],
+            retries: 0,
+            timing: None,
+            sending: None,
             scroll: 0,
+            area: Rect::default(),
+            tabs_area: Rect::default(),
+            content_area: Rect::default(),
+            header_order: HeaderOrder::default(),
+            header_state: TableState::default(),
+            extracted: vec![],
+            extract_errors: vec![],
+            error: None,
+        }
+    }
+
+    /// Shows a text, like an error, in the Content tab
+    pub fn set_response(&mut self, text: String) {
+        self.set_text(text, Language::Plain);
+        self.value = None;
+        self.json = None;
+    }
+
+    /// Shows the body of a response, highlighted by the content type of the response
+    pub fn set_body(&mut self, body: String) {
+        let content_type = self
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let language = Language::detect(content_type, &body);
+        self.set_text(body, language);
+        self.value = None;
+        self.json = None;
+    }
+
+    /// Shows a JSON body as a tree, or pretty-printed in the raw view, after applying the filter
+    pub fn set_json(&mut self, value: serde_json::Value) {
+        self.value = Some(value);
+        self.json = None;
+        self.apply_filter();
+    }
+
+    /// Shows the filtered response, or keeps showing the last result when the filter fails
+    fn apply_filter(&mut self) {
+        let Some(value) = &self.value else {
+            return;
+        };
+        match self.filter.apply(value) {
+            Some(filtered) => self.show_json(filtered),
+            None if self.json.is_none() => self.show_json(value.clone()),
+            None => (),
+        }
+    }
+
+    fn show_json(&mut self, value: serde_json::Value) {
+        let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+        self.set_text(text, Language::Json);
+        self.json = Some(JsonTreeState::new(value, self.theme.clone()));
+        self.scroll = 0;
+    }
+
+    fn set_text(&mut self, text: String, language: Language) {
+        self.tokens = highlight(language, &text);
+        self.response = text;
+        self.search.update(&self.response);
+        self.restyle();
+    }
+
+    /// Styles the response text with its syntax tokens and the search matches
+    pub fn restyle(&mut self) {
+        let mut marks = vec![vec![]; self.tokens.len()];
+        for (i, m) in self.search.matches.iter().enumerate() {
+            let style = if i == self.search.current {
+                self.theme.current_match()
+            } else {
+                self.theme.search_match()
+            };
+            marks[m.line].push((m.range.clone(), style));
+        }
+        let lines: Vec<_> = self
+            .tokens
+            .iter()
+            .zip(self.response.lines())
+            .zip(&marks)
+            .map(|((tokens, line), marks)| self.theme.highlight(line, tokens, marks))
+            .collect();
+        self.text = Text::from(lines);
+    }
+
+    fn select_match(&mut self, offset: isize) {
+        self.search.select(offset);
+        self.raw = true;
+        self.restyle();
+        self.scroll_to_match = true;
+    }
+
+    /// Rows the first `lines` lines take when they are wrapped to `width`
+    fn wrapped_rows(&self, lines: usize, width: u16) -> usize {
+        let width = width.max(1) as usize;
+        self.response
+            .lines()
+            .take(lines)
+            .map(|line| line.width().max(1).div_ceil(width))
+            .sum()
+    }
+
+    /// Scrolls the wrapped text so that the current match is visible
+    fn scroll_to(&mut self, line: usize, area: Rect) {
+        let row = self.wrapped_rows(line, area.width);
+        let height = area.height as usize;
+        let top = self.scroll as usize;
+        if row < top || row >= top + height {
+            self.scroll = row.saturating_sub(height / 3).min(u16::MAX as usize) as u16;
         }
     }
 
+    /// Replaces the headers of the last response
+    pub fn set_headers(&mut self, headers: HeaderMap) {
+        self.headers = headers;
+        self.header_state = TableState::default();
+    }
+
+    /// One row per header value, so multi-valued headers show up multiple times
+    fn header_rows(&self) -> Vec<(String, String)> {
+        let mut rows: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        match self.header_order {
+            HeaderOrder::Received => (),
+            HeaderOrder::Ascending => rows.sort_by(|a, b| a.0.cmp(&b.0)),
+            HeaderOrder::Descending => rows.sort_by(|a, b| b.0.cmp(&a.0)),
+        }
+        rows
+    }
+
+    fn select_header(&mut self, offset: isize) {
+        let len = self.headers.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let i = match self.header_state.selected() {
+            Some(i) => (i as isize + offset).rem_euclid(len),
+            None => 0,
+        };
+        self.header_state.select(Some(i as usize));
+    }
+
     pub fn next(&mut self) {
         self.tab_index = (self.tab_index + 1) % Self::TAB_LEN;
     }
@@ -112,24 +436,67 @@ impl ResponseState {
 
         self.tab_index = index;
     }
+
+    /// Selects the tab at the position of a click
+    pub fn click(&mut self, column: u16, row: u16) {
+        if let Some(i) = mouse::tab_at(Response::OPTIONS, self.tabs_area, column, row) {
+            self.select(i);
+        }
+    }
+
+    /// Scrolls the content by `rows` of text, or moves the selection of the tree and the headers
+    pub fn scroll_by(&mut self, rows: i16) {
+        match Response::OPTIONS[self.tab_index] {
+            "Content" => match self.json.as_mut() {
+                Some(json) if !self.raw => json.scroll_by(rows),
+                _ => {
+                    let area = self.content_area;
+                    let bottom = self
+                        .wrapped_rows(usize::MAX, area.width)
+                        .saturating_sub(area.height as usize)
+                        .min(u16::MAX as usize) as u16;
+                    self.scroll = self.scroll.saturating_add_signed(rows).min(bottom);
+                }
+            },
+            "Headers" => self.select_header(rows.signum() as isize),
+            _ => (),
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct Response;
 
 impl Response {
-    const OPTIONS: &'static [&'static str] = &["Content", "Headers", "Cookies"];
+    const OPTIONS: &'static [&'static str] =
+        &["Content", "Headers", "Cookies", "Redirects", "Timing"];
+    const SPINNER: &'static [&'static str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 }
 
 impl StatefulWidget for Response {
     type State = ResponseState;
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let mut title = vec![Span::raw("Response")];
+        if !state.extracted.is_empty() {
+            title.push(Span::styled(
+                format!(" Set: {}", state.extracted.join(", ")),
+                state.theme.focused(),
+            ));
+        }
+        if !state.extract_errors.is_empty() {
+            title.push(Span::styled(
+                format!(" Not extracted: {}", state.extract_errors.join(", ")),
+                state.theme.error(),
+            ));
+        }
         let block = Block::default()
-            .title("Response")
+            .title(Spans::from(title))
             .borders(Borders::ALL)
-            .style(state.theme.block(state.active));
+            .style(state.theme.block(state.active))
+            .border_style(state.theme.border(state.active));
         let request_area = block.inner(area);
         block.render(area, buf);
+        state.area = area;
 
         let titles = Self::OPTIONS.iter().cloned().map(Spans::from).collect();
         let chunks = Layout::default()
@@ -144,12 +511,54 @@ impl StatefulWidget for Response {
             )
             .split(request_area);
 
-        let response_text = Paragraph::new(state.response.clone())
+        let mut content_area = chunks[2];
+        if state.value.is_some()
+            && state.filter.visible()
+            && Self::OPTIONS[state.tab_index] == "Content"
+        {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                .split(content_area);
+            StatefulWidget::render(FilterLine, rows[0], buf, &mut state.filter);
+            content_area = rows[1];
+        }
+        state.tabs_area = chunks[1];
+        state.content_area = content_area;
+        if state.scroll_to_match {
+            state.scroll_to_match = false;
+            if let Some(line) = state.search.current().map(|m| m.line) {
+                state.scroll_to(line, content_area);
+            }
+        }
+        let response_text = Paragraph::new(state.text.clone())
             .style(state.theme.block(state.active()))
             .scroll((state.scroll, 0))
             .wrap(Wrap { trim: false });
-        Widget::render(
-            Table::new([Row::new([Cell::from(Spans::from(vec![
+        let status = match state.sending {
+            Some(started) => {
+                let elapsed = started.elapsed();
+                let frame = (elapsed.as_millis() / 100) as usize % Self::SPINNER.len();
+                vec![
+                    Span::styled(
+                        format!(" {} Sending… ", Self::SPINNER[frame]),
+                        state.theme.selected(),
+                    ),
+                    Span::styled(
+                        format!("{:.1} s", elapsed.as_secs_f32()),
+                        state.theme.focused(),
+                    ),
+                    Span::styled(
+                        state
+                            .keymap
+                            .key(Binding::Global(GlobalKeyAction::Cancel))
+                            .map(|key| format!(" ({key} to cancel)"))
+                            .unwrap_or_default(),
+                        state.theme.placeholder(),
+                    ),
+                ]
+            }
+            None => vec![
                 Span::raw(" Status: "),
                 Span::styled(
                     state.status_code.as_str(),
@@ -160,17 +569,176 @@ impl StatefulWidget for Response {
                     state.time.as_millis().to_string() + " ms",
                     state.theme.focused(),
                 ),
-            ]))])])
-            .widths(&[Constraint::Length(40)]),
-            chunks[0],
-            buf,
-        );
+                Span::styled(
+                    match state.reused {
+                        Some(true) => " (reused connection)",
+                        Some(false) => " (new connection)",
+                        None => "",
+                    },
+                    state.theme.placeholder(),
+                ),
+                Span::styled(
+                    match (state.redirects.len(), state.retries) {
+                        (0, 0) => String::new(),
+                        (redirects, 0) => format!(" Redirects: {redirects}"),
+                        (0, retries) => format!(" Retries: {retries}"),
+                        (redirects, retries) => {
+                            format!(" Redirects: {redirects} Retries: {retries}")
+                        }
+                    },
+                    state.theme.focused(),
+                ),
+            ],
+        };
+        if state.search.prompt_open() {
+            StatefulWidget::render(SearchPrompt, chunks[0], buf, &mut state.search);
+        } else {
+            let mut status = status;
+            status.extend(state.search.status());
+            if let Some(error) = &state.error {
+                status.push(Span::styled(format!(" {error}"), state.theme.error()));
+            }
+            Widget::render(
+                Table::new([Row::new([Cell::from(Spans::from(status))])])
+                    .widths(&[Constraint::Percentage(100)]),
+                chunks[0],
+                buf,
+            );
+        }
         Tabs::new(titles)
             .block(Block::default().borders(Borders::ALL))
             .select(state.tab_index)
-            .highlight_style(state.theme.selected())
+            .highlight_style(state.theme.tab_selected())
             .divider("|")
             .render(chunks[1], buf);
-        response_text.render(chunks[2], buf);
+
+        match Self::OPTIONS[state.tab_index] {
+            "Headers" => {
+                let name = match state.header_order {
+                    HeaderOrder::Received => "Name",
+                    HeaderOrder::Ascending => "Name ▲",
+                    HeaderOrder::Descending => "Name ▼",
+                };
+                let rows = state.header_rows();
+                let table = Table::new(
+                    rows.into_iter()
+                        .map(|(name, value)| Row::new([Cell::from(name), Cell::from(value)])),
+                )
+                .header(Row::new([name, "Value"]).style(state.theme.focused()))
+                .style(state.theme.block(state.active))
+                .widths(&[Constraint::Percentage(35), Constraint::Percentage(65)])
+                .highlight_style(state.theme.selected());
+                StatefulWidget::render(table, chunks[2], buf, &mut state.header_state);
+            }
+            "Cookies" => StatefulWidget::render(Cookies, chunks[2], buf, &mut state.cookies),
+            "Timing" => match &state.timing {
+                Some(timing) => render_timing(timing, chunks[2], buf, &state.theme),
+                None => Paragraph::new("No timing of the response")
+                    .style(state.theme.placeholder())
+                    .render(chunks[2], buf),
+            },
+            "Redirects" if state.redirects.is_empty() => {
+                Paragraph::new("No redirects were followed")
+                    .style(state.theme.placeholder())
+                    .render(chunks[2], buf)
+            }
+            "Redirects" => Widget::render(
+                Table::new(state.redirects.iter().map(|redirect| {
+                    Row::new([
+                        Cell::from(redirect.status.as_str())
+                            .style(state.theme.status_code(redirect.status.as_u16())),
+                        Cell::from(redirect.url.as_str()),
+                        Cell::from(redirect.location.as_str()),
+                    ])
+                }))
+                .header(Row::new(["Status", "URL", "Location"]).style(state.theme.focused()))
+                .style(state.theme.block(state.active))
+                .widths(&[
+                    Constraint::Length(6),
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(50),
+                ]),
+                chunks[2],
+                buf,
+            ),
+            _ => match state.json.as_mut() {
+                Some(json) if !state.raw => {
+                    StatefulWidget::render(JsonTree, content_area, buf, json)
+                }
+                _ => response_text.render(content_area, buf),
+            },
+        }
+    }
+}
+
+/// Phases of a response with a bar for each, bars start where the previous phase ended
+fn render_timing(timing: &Timing, area: Rect, buf: &mut Buffer, theme: &GlobalTheme) {
+    let mut phases = vec![];
+    // Opening a connection has no phases when one was reused
+    for (name, time) in [
+        ("Redirects", timing.redirects),
+        ("DNS lookup", timing.dns),
+        ("TCP connect", timing.connect),
+        ("TLS handshake", timing.tls),
+    ] {
+        if !time.is_zero() {
+            phases.push((name, time));
+        }
+    }
+    phases.push(("Waiting (TTFB)", timing.first_byte));
+    phases.push(("Download", timing.download));
+
+    let total: Duration = phases.iter().map(|(_, time)| *time).sum();
+    let bar_width = area.width.saturating_sub(28);
+    let mut start = Duration::ZERO;
+    let mut rows: Vec<_> = phases
+        .into_iter()
+        .map(|(name, time)| {
+            let share = |time: Duration| match total.as_secs_f64() {
+                total if total > 0.0 => {
+                    (time.as_secs_f64() / total * bar_width as f64).round() as usize
+                }
+                _ => 0,
+            };
+            let offset = share(start);
+            start += time;
+            let bar = format!(
+                "{}{}",
+                " ".repeat(offset),
+                "█".repeat(share(start) - offset)
+            );
+            Row::new([
+                Cell::from(name),
+                Cell::from(format_duration(time)),
+                Cell::from(bar).style(theme.focused()),
+            ])
+        })
+        .collect();
+    rows.push(Row::new([
+        Cell::from("Total"),
+        Cell::from(format_duration(timing.total)),
+    ]));
+    rows.push(Row::new([""]));
+    rows.push(Row::new([
+        Cell::from("Sent"),
+        Cell::from(format!("{} B", timing.bytes_sent)),
+    ]));
+    rows.push(Row::new([
+        Cell::from("Received"),
+        Cell::from(format!("{} B", timing.bytes_received)),
+    ]));
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(10),
+        Constraint::Length(bar_width),
+    ];
+    let table = Table::new(rows).style(theme.block(false)).widths(&widths);
+    Widget::render(table, area, buf);
+}
+
+fn format_duration(time: Duration) -> String {
+    match time.as_micros() {
+        micros if micros < 10_000 => format!("{:.2} ms", micros as f64 / 1000.0),
+        _ => format!("{} ms", time.as_millis()),
     }
 }
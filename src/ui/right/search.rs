@@ -0,0 +1,202 @@
+use crossterm::event::KeyEvent;
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Paragraph, StatefulWidget, Widget},
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    keys::{self, Binding, GlobalKeymap, NormalKeyAction},
+    search::{self, Match, SearchOptions},
+    ui::theme::GlobalTheme,
+};
+
+/// Search in the response text
+pub struct SearchState {
+    /// Prompt for the query while it is typed
+    input: Option<TextArea<'static>>,
+    options: SearchOptions,
+    query: String,
+    pub matches: Vec<Match>,
+    /// Index in `matches` of the current match
+    pub current: usize,
+    error: Option<String>,
+    theme: GlobalTheme,
+    keymap: GlobalKeymap,
+}
+
+impl SearchState {
+    pub fn new(theme: GlobalTheme, keymap: GlobalKeymap) -> Self {
+        Self {
+            input: None,
+            options: SearchOptions::default(),
+            query: String::new(),
+            matches: vec![],
+            current: 0,
+            error: None,
+            theme,
+            keymap,
+        }
+    }
+
+    pub fn prompt_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Whether there is a query, the matches are shown while there is one
+    pub fn active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    /// Opens the prompt with the last query
+    pub fn open(&mut self) {
+        let mut input = TextArea::from([self.query.as_str()]);
+        input.move_cursor(CursorMove::End);
+        input.set_cursor_line_style(Default::default());
+        self.input = Some(input);
+    }
+
+    /// Handles a key while the prompt is open, returns whether the matches changed
+    pub fn handle_key(
+        &mut self,
+        key_event: KeyEvent,
+        binding: Option<Binding>,
+        text: &str,
+    ) -> bool {
+        let Some(input) = &mut self.input else {
+            return false;
+        };
+        match keys::input_action(key_event, binding) {
+            NormalKeyAction::Exit => {
+                self.input = None;
+                self.clear();
+            }
+            NormalKeyAction::Accept => {
+                self.input = None;
+                return false;
+            }
+            NormalKeyAction::ToggleCase => {
+                self.options.case_sensitive = !self.options.case_sensitive;
+                self.update(text);
+            }
+            NormalKeyAction::ToggleRegex => {
+                self.options.regex = !self.options.regex;
+                self.update(text);
+            }
+            _ => {
+                if !input.input(key_event) {
+                    return false;
+                }
+                self.query = input.lines().join("");
+                self.update(text);
+            }
+        }
+        true
+    }
+
+    /// Searches `text` again, the first match becomes the current one
+    pub fn update(&mut self, text: &str) {
+        self.current = 0;
+        match search::find(text, &self.query, self.options) {
+            Ok(matches) => {
+                self.matches = matches;
+                self.error = None;
+            }
+            Err(e) => {
+                self.matches.clear();
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.matches.clear();
+        self.error = None;
+    }
+
+    /// Moves to the next match, or the previous one for a negative offset
+    pub fn select(&mut self, offset: isize) {
+        let len = self.matches.len() as isize;
+        if len > 0 {
+            self.current = (self.current as isize + offset).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn current(&self) -> Option<&Match> {
+        self.matches.get(self.current)
+    }
+
+    /// Match counter for the status line
+    pub fn status(&self) -> Vec<Span<'static>> {
+        if let Some(error) = &self.error {
+            return vec![Span::styled(format!(" {error}"), self.theme.error())];
+        }
+        if !self.active() {
+            return vec![];
+        }
+        let counter = match self.matches.len() {
+            0 => String::from(" No matches"),
+            len => format!(" Match {}/{len}", self.current + 1),
+        };
+        let style = if self.matches.is_empty() {
+            self.theme.error()
+        } else {
+            self.theme.focused()
+        };
+        vec![Span::styled(counter, style)]
+    }
+}
+
+/// The search prompt, shown in the status line of the response
+#[derive(Default)]
+pub struct SearchPrompt;
+
+impl StatefulWidget for SearchPrompt {
+    type State = SearchState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let theme = &state.theme;
+        let toggle = |label: &'static str, enabled: bool| {
+            Span::styled(
+                label,
+                if enabled {
+                    theme.selected()
+                } else {
+                    theme.placeholder()
+                },
+            )
+        };
+        let mut info = vec![
+            toggle(" [Aa]", state.options.case_sensitive),
+            toggle(" [.*]", state.options.regex),
+        ];
+        info.extend(state.status());
+        let hint = state.keymap.hint(&[
+            (Binding::Normal(NormalKeyAction::ToggleCase), "case"),
+            (Binding::Normal(NormalKeyAction::ToggleRegex), "regex"),
+        ]);
+        info.push(Span::styled(format!(" {hint}"), theme.placeholder()));
+        let info = Spans::from(info);
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(10),
+                    Constraint::Length(info.width() as u16),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        Paragraph::new(Span::styled(" / ", theme.focused())).render(chunks[0], buf);
+        if let Some(input) = &mut state.input {
+            input.set_cursor_style(theme.cursor());
+            Widget::render(input.widget(), chunks[1], buf);
+        }
+        Paragraph::new(info).render(chunks[2], buf);
+    }
+}
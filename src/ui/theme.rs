@@ -1,14 +1,36 @@
-use std::rc::Rc;
+use std::{cell::Cell, collections::BTreeMap, ops::Range, rc::Rc};
 
-use tui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+};
+
+use crate::highlight::{Token, TokenKind};
 
 pub type GlobalTheme = Rc<Theme>;
 
-#[derive(Debug)]
-pub struct Theme {
+/// Names of the built-in themes, the first one is the default
+const BUILTIN_THEMES: &[&str] = &["dark", "light"];
+
+/// Colours of a theme
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
     selected: Color,
     focused: Color,
     disabled: Color,
+    error: Color,
+    placeholder: Color,
+    border: Color,
+    border_focused: Color,
+    tab_selected: Color,
+    cursor_bg: Color,
+    cursor_fg: Color,
+    match_bg: Color,
+    match_fg: Color,
+    current_match_bg: Color,
+    current_match_fg: Color,
 
     status_500_bg: Color,
     status_500_fg: Color,
@@ -20,14 +42,40 @@ pub struct Theme {
     status_200_fg: Color,
     status_100_bg: Color,
     status_100_fg: Color,
+
+    syntax_key: Color,
+    syntax_string: Color,
+    syntax_number: Color,
+    syntax_keyword: Color,
+    syntax_punctuation: Color,
+    syntax_comment: Color,
+    syntax_tag: Color,
+    syntax_attribute: Color,
 }
 
-impl Default for Theme {
+impl Default for Palette {
     fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Palette {
+    pub fn dark() -> Self {
         Self {
             selected: Color::Yellow,
             focused: Color::White,
             disabled: Color::DarkGray,
+            error: Color::Red,
+            placeholder: Color::DarkGray,
+            border: Color::DarkGray,
+            border_focused: Color::White,
+            tab_selected: Color::Yellow,
+            cursor_bg: Color::White,
+            cursor_fg: Color::Black,
+            match_bg: Color::DarkGray,
+            match_fg: Color::White,
+            current_match_bg: Color::Yellow,
+            current_match_fg: Color::Black,
 
             status_500_bg: Color::Rgb(255, 62, 62),
             status_500_fg: Color::Rgb(255, 255, 255),
@@ -39,31 +87,289 @@ impl Default for Theme {
             status_200_fg: Color::Rgb(255, 255, 255),
             status_100_bg: Color::Rgb(75, 151, 255),
             status_100_fg: Color::Rgb(255, 255, 255),
+
+            syntax_key: Color::Cyan,
+            syntax_string: Color::Green,
+            syntax_number: Color::Magenta,
+            syntax_keyword: Color::LightBlue,
+            syntax_punctuation: Color::Gray,
+            syntax_comment: Color::DarkGray,
+            syntax_tag: Color::LightBlue,
+            syntax_attribute: Color::Cyan,
+        }
+    }
+
+    /// For terminals with a light background
+    pub fn light() -> Self {
+        Self {
+            selected: Color::Blue,
+            focused: Color::Black,
+            disabled: Color::DarkGray,
+            error: Color::Red,
+            placeholder: Color::DarkGray,
+            border: Color::DarkGray,
+            border_focused: Color::Black,
+            tab_selected: Color::Blue,
+            cursor_bg: Color::Black,
+            cursor_fg: Color::White,
+            match_bg: Color::LightYellow,
+            match_fg: Color::Black,
+            current_match_bg: Color::Blue,
+            current_match_fg: Color::White,
+            syntax_key: Color::Blue,
+            syntax_string: Color::Green,
+            syntax_number: Color::Magenta,
+            syntax_keyword: Color::Red,
+            syntax_punctuation: Color::Black,
+            syntax_comment: Color::DarkGray,
+            syntax_tag: Color::Blue,
+            syntax_attribute: Color::Magenta,
+            ..Self::dark()
         }
     }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    /// Sets a colour by its name in the config file
+    fn set(&mut self, name: &str, color: Color) -> Result<(), String> {
+        let field = match name {
+            "selected" => &mut self.selected,
+            "focused" => &mut self.focused,
+            "disabled" => &mut self.disabled,
+            "error" => &mut self.error,
+            "placeholder" => &mut self.placeholder,
+            "border" => &mut self.border,
+            "border_focused" => &mut self.border_focused,
+            "tab_selected" => &mut self.tab_selected,
+            "cursor_bg" => &mut self.cursor_bg,
+            "cursor_fg" => &mut self.cursor_fg,
+            "match_bg" => &mut self.match_bg,
+            "match_fg" => &mut self.match_fg,
+            "current_match_bg" => &mut self.current_match_bg,
+            "current_match_fg" => &mut self.current_match_fg,
+            "status_500_bg" => &mut self.status_500_bg,
+            "status_500_fg" => &mut self.status_500_fg,
+            "status_400_bg" => &mut self.status_400_bg,
+            "status_400_fg" => &mut self.status_400_fg,
+            "status_300_bg" => &mut self.status_300_bg,
+            "status_300_fg" => &mut self.status_300_fg,
+            "status_200_bg" => &mut self.status_200_bg,
+            "status_200_fg" => &mut self.status_200_fg,
+            "status_100_bg" => &mut self.status_100_bg,
+            "status_100_fg" => &mut self.status_100_fg,
+            "syntax_key" => &mut self.syntax_key,
+            "syntax_string" => &mut self.syntax_string,
+            "syntax_number" => &mut self.syntax_number,
+            "syntax_keyword" => &mut self.syntax_keyword,
+            "syntax_punctuation" => &mut self.syntax_punctuation,
+            "syntax_comment" => &mut self.syntax_comment,
+            "syntax_tag" => &mut self.syntax_tag,
+            "syntax_attribute" => &mut self.syntax_attribute,
+            _ => return Err(format!("Unknown colour '{name}'")),
+        };
+        *field = color;
+        Ok(())
+    }
+}
+
+/// A colour in the config file: a name like `lightblue`, `#rrggbb` or a 256-colour index
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigColor {
+    Name(String),
+    Index(u8),
+}
+
+impl TryFrom<&ConfigColor> for Color {
+    type Error = String;
+
+    fn try_from(color: &ConfigColor) -> Result<Self, Self::Error> {
+        let name = match color {
+            ConfigColor::Index(i) => return Ok(Color::Indexed(*i)),
+            ConfigColor::Name(name) => name,
+        };
+        if let Some(hex) = name.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+                _ => Err(format!("Invalid colour '{name}', expected #rrggbb")),
+            };
+        }
+        if let Ok(i) = name.parse() {
+            return Ok(Color::Indexed(i));
+        }
+        let normalized: String = name
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_lowercase()
+            .replace("grey", "gray");
+        Ok(match normalized.as_str() {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" => Color::Gray,
+            "darkgray" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => return Err(format!("Unknown colour '{name}'")),
+        })
+    }
+}
+
+/// The themes that can be switched between
+#[derive(Debug)]
+pub struct Themes {
+    list: Vec<(String, Palette)>,
+    current: usize,
+}
+
+impl Themes {
+    /// Loads the themes of the config and selects the theme named `selected`, or the default theme
+    pub fn new(
+        config: &BTreeMap<String, BTreeMap<String, ConfigColor>>,
+        selected: Option<&str>,
+    ) -> Result<Self, String> {
+        let list = themes(config)?;
+        let current = match selected {
+            Some(name) => list
+                .iter()
+                .position(|(theme, _)| theme == name)
+                .ok_or_else(|| format!("Unknown theme '{name}'"))?,
+            None => 0,
+        };
+        Ok(Self { list, current })
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.list[self.current].1
+    }
+
+    /// Switches to the next theme and returns its palette
+    pub fn next(&mut self) -> Palette {
+        self.current = (self.current + 1) % self.list.len();
+        self.palette()
+    }
+}
+
+/// The built-in themes followed by the themes of the config, each user theme starts from its
+/// `base` theme, or the default theme. All errors are reported at once.
+fn themes(
+    config: &BTreeMap<String, BTreeMap<String, ConfigColor>>,
+) -> Result<Vec<(String, Palette)>, String> {
+    let mut themes: Vec<_> = BUILTIN_THEMES
+        .iter()
+        .filter_map(|name| Some((name.to_string(), Palette::builtin(name)?)))
+        .collect();
+    let mut errors = vec![];
+    for (name, colors) in config {
+        let mut palette = match colors.get("base") {
+            None => Palette::default(),
+            Some(ConfigColor::Name(base)) => match Palette::builtin(base) {
+                Some(palette) => palette,
+                None => {
+                    errors.push(format!("{name}: Unknown base theme '{base}'"));
+                    continue;
+                }
+            },
+            Some(ConfigColor::Index(_)) => {
+                errors.push(format!("{name}: The base has to be the name of a theme"));
+                continue;
+            }
+        };
+        for (field, color) in colors.iter().filter(|(field, _)| *field != "base") {
+            if let Err(e) = Color::try_from(color).and_then(|color| palette.set(field, color)) {
+                errors.push(format!("{name}: {e}"));
+            }
+        }
+        match themes.iter_mut().find(|(theme, _)| theme == name) {
+            Some((_, builtin)) => *builtin = palette,
+            None => themes.push((name.clone(), palette)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(themes)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// The styles of the client, the palette can be switched while running
+#[derive(Debug, Default)]
+pub struct Theme {
+    palette: Cell<Palette>,
 }
 
 impl Theme {
+    pub fn new(palette: Palette) -> Self {
+        Self {
+            palette: Cell::new(palette),
+        }
+    }
+
+    pub fn set_palette(&self, palette: Palette) {
+        self.palette.set(palette);
+    }
+
+    fn colors(&self) -> Palette {
+        self.palette.get()
+    }
+
     pub fn block(&self, focused: bool) -> Style {
         if focused {
-            Style::default().fg(self.focused)
+            Style::default().fg(self.colors().focused)
+        } else {
+            Style::default().fg(self.colors().disabled)
+        }
+    }
+
+    pub fn border(&self, focused: bool) -> Style {
+        if focused {
+            Style::default().fg(self.colors().border_focused)
         } else {
-            Style::default().fg(self.disabled)
+            Style::default().fg(self.colors().border)
         }
     }
 
     pub fn selected(&self) -> Style {
-        Style::default().fg(self.selected)
+        Style::default().fg(self.colors().selected)
+    }
+
+    pub fn tab_selected(&self) -> Style {
+        Style::default().fg(self.colors().tab_selected)
     }
 
     pub fn focused(&self) -> Style {
-        Style::default().fg(self.focused)
+        Style::default().fg(self.colors().focused)
+    }
+
+    pub fn error(&self) -> Style {
+        Style::default().fg(self.colors().error)
     }
 
     pub fn placeholder(&self) -> Style {
         Style::default()
             .add_modifier(Modifier::ITALIC)
-            .fg(self.disabled)
+            .fg(self.colors().placeholder)
     }
 
     pub fn hostname(&self) -> Style {
@@ -71,18 +377,138 @@ impl Theme {
     }
 
     pub fn cursor(&self) -> Style {
-        Style::default().bg(Color::White).fg(Color::Black)
+        let colors = self.colors();
+        Style::default().bg(colors.cursor_bg).fg(colors.cursor_fg)
     }
 
     pub fn status_code(&self, code: u16) -> Style {
+        let colors = self.colors();
         let style = Style::default().add_modifier(Modifier::BOLD);
         match code {
-            c if c / 500 == 1 => style.bg(self.status_500_bg).fg(self.status_500_fg),
-            c if c / 400 == 1 => style.bg(self.status_400_bg).fg(self.status_400_fg),
-            c if c / 300 == 1 => style.bg(self.status_300_bg).fg(self.status_300_fg),
-            c if c / 200 == 1 => style.bg(self.status_200_bg).fg(self.status_200_fg),
-            c if c / 100 == 1 => style.bg(self.status_100_bg).fg(self.status_100_fg),
+            c if c / 500 == 1 => style.bg(colors.status_500_bg).fg(colors.status_500_fg),
+            c if c / 400 == 1 => style.bg(colors.status_400_bg).fg(colors.status_400_fg),
+            c if c / 300 == 1 => style.bg(colors.status_300_bg).fg(colors.status_300_fg),
+            c if c / 200 == 1 => style.bg(colors.status_200_bg).fg(colors.status_200_fg),
+            c if c / 100 == 1 => style.bg(colors.status_100_bg).fg(colors.status_100_fg),
             _ => style.bg(Color::White).fg(Color::Black),
         }
     }
+
+    pub fn syntax(&self, kind: TokenKind) -> Style {
+        let colors = self.colors();
+        let style = Style::default();
+        match kind {
+            TokenKind::Key => style.fg(colors.syntax_key),
+            TokenKind::String => style.fg(colors.syntax_string),
+            TokenKind::Number => style.fg(colors.syntax_number),
+            TokenKind::Keyword => style.fg(colors.syntax_keyword),
+            TokenKind::Punctuation => style.fg(colors.syntax_punctuation),
+            TokenKind::Comment => style
+                .fg(colors.syntax_comment)
+                .add_modifier(Modifier::ITALIC),
+            TokenKind::Tag => style.fg(colors.syntax_tag),
+            TokenKind::Attribute => style.fg(colors.syntax_attribute),
+        }
+    }
+
+    pub fn search_match(&self) -> Style {
+        let colors = self.colors();
+        Style::default().bg(colors.match_bg).fg(colors.match_fg)
+    }
+
+    pub fn current_match(&self) -> Style {
+        let colors = self.colors();
+        Style::default()
+            .bg(colors.current_match_bg)
+            .fg(colors.current_match_fg)
+    }
+
+    /// Styles the tokens of a highlighted line, `marks` like search matches are styled on top.
+    /// Both have to be sorted by their range.
+    pub fn highlight(
+        &self,
+        line: &str,
+        tokens: &[Token],
+        marks: &[(Range<usize>, Style)],
+    ) -> Spans<'static> {
+        let mut bounds = vec![0, line.len()];
+        bounds.extend(tokens.iter().flat_map(|t| [t.range.start, t.range.end]));
+        bounds.extend(marks.iter().flat_map(|(range, _)| [range.start, range.end]));
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let (mut t, mut m) = (0, 0);
+        let mut spans = Vec::with_capacity(bounds.len());
+        for segment in bounds.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            while t < tokens.len() && tokens[t].range.end <= start {
+                t += 1;
+            }
+            while m < marks.len() && marks[m].0.end <= start {
+                m += 1;
+            }
+            let mut style = match tokens.get(t) {
+                Some(token) if token.range.start <= start => self.syntax(token.kind),
+                _ => Style::default(),
+            };
+            if let Some((range, mark)) = marks.get(m) {
+                if range.start <= start {
+                    style = style.patch(*mark);
+                }
+            }
+            spans.push(Span::styled(line[start..end].to_string(), style));
+        }
+        Spans::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tui::style::Color;
+
+    use super::{themes, ConfigColor, Themes};
+
+    #[test]
+    fn themes_from_config() {
+        let color = |name: &str| Color::try_from(&ConfigColor::Name(name.to_string()));
+        assert_eq!(color("#ff8000"), Ok(Color::Rgb(255, 128, 0)));
+        assert_eq!(color("Light_Blue"), Ok(Color::LightBlue));
+        assert_eq!(color("dark grey"), Ok(Color::DarkGray));
+        assert_eq!(color("208"), Ok(Color::Indexed(208)));
+        assert_eq!(
+            Color::try_from(&ConfigColor::Index(17)),
+            Ok(Color::Indexed(17))
+        );
+        assert!(color("#ff80").is_err());
+        assert!(color("purple").is_err());
+
+        let config = |json: &str| {
+            let config: BTreeMap<String, BTreeMap<String, ConfigColor>> =
+                serde_json::from_str(json).unwrap();
+            themes(&config)
+        };
+        let list = config(r##"{"mine": {"base": "light", "selected": "#00ff00"}}"##).unwrap();
+        let names: Vec<_> = list.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["dark", "light", "mine"]);
+        assert_eq!(list[2].1.selected, Color::Rgb(0, 255, 0));
+        assert_eq!(list[2].1.focused, Color::Black);
+
+        let config_themes = serde_json::from_str(r#"{"mine": {}}"#).unwrap();
+        let mut list = Themes::new(&config_themes, Some("light")).unwrap();
+        assert_eq!(list.palette().focused, Color::Black);
+        assert_eq!(list.next().focused, Color::White);
+        assert_eq!(list.next().selected, Color::Yellow);
+        assert_eq!(list.current, 0);
+        assert!(Themes::new(&config_themes, Some("solarized")).is_err());
+
+        let errors =
+            config(r##"{"a": {"base": "solarized"}, "b": {"selectd": "red", "error": "#12"}}"##)
+                .unwrap_err();
+        assert_eq!(
+            errors,
+            "a: Unknown base theme 'solarized'\nb: Invalid colour '#12', expected #rrggbb\nb: Unknown colour 'selectd'"
+        );
+    }
 }